@@ -1,10 +1,21 @@
-use mediatoascii::video::{VideoConfig, VideoResult};
+use mediatoascii::video::{process_video_job_async, VideoConfig, VideoJob, VideoResult};
 use serde::Serialize;
+use std::collections::HashMap;
 use std::path::Path;
-use tauri::{AppHandle, Emitter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
+
+/// Video jobs that are running, by job id, so they can be cancelled
+#[derive(Default)]
+struct VideoJobs {
+    next_id: AtomicU64,
+    running: Mutex<HashMap<u64, VideoJob>>,
+}
 
 #[derive(Clone, Serialize)]
 struct VideoProgressInfo {
+    job_id: u64,
     percentage: f32,
     current_read_frame: u64,
     current_encode_frame: u64,
@@ -12,40 +23,57 @@ struct VideoProgressInfo {
     total_frames: u64,
 }
 
+impl VideoProgressInfo {
+    fn new(job_id: u64, job: &VideoJob) -> Self {
+        let progress = job.progress();
+        Self {
+            job_id,
+            percentage: progress.percentage(),
+            current_read_frame: progress.read_current_frame(),
+            current_encode_frame: progress.encode_current_frame(),
+            current_write_frame: progress.write_current_frame(),
+            total_frames: progress.total_frames(),
+        }
+    }
+}
+
 #[tauri::command]
 fn file_exists(path: &str) -> bool {
     Path::new(path).exists()
 }
 
+/// Processes a video as a new job, emitting its progress as `video-progress` events until it finishes
 #[tauri::command]
-async fn process_video(config: VideoConfig) -> VideoResult<()> {
-    mediatoascii::video::process_video(config).inspect_err(|err| eprintln!("{:?}", err))
-}
+async fn process_video(app: AppHandle, jobs: State<'_, VideoJobs>, config: VideoConfig) -> VideoResult<()> {
+    let job_id = jobs.next_id.fetch_add(1, Ordering::Relaxed);
+    let job = VideoJob::new();
+    jobs.running.lock().unwrap().insert(job_id, job.clone());
 
-#[tauri::command]
-async fn video_progress(app: AppHandle) {
-    unsafe {
+    let progress_job = job.clone();
+    let progress_app = app.clone();
+    let progress = tokio::spawn(async move {
         loop {
-            app.emit(
-                "video-progress",
-                VideoProgressInfo {
-                    percentage: mediatoascii::video::PROGRESS_PERCENTAGE,
-                    current_read_frame: mediatoascii::video::READ_CURRENT_FRAME,
-                    current_encode_frame: mediatoascii::video::ENCODE_CURRENT_FRAME,
-                    current_write_frame: mediatoascii::video::WRITE_CURRENT_FRAME,
-                    total_frames: mediatoascii::video::TOTAL_FRAMES,
-                },
-            )
-            .unwrap();
+            progress_app.emit("video-progress", VideoProgressInfo::new(job_id, &progress_job)).unwrap();
             tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         }
-    }
+    });
+
+    // Cancels the job if the command is dropped before it finishes
+    let res = process_video_job_async(config, job.clone()).await;
+
+    progress.abort();
+    jobs.running.lock().unwrap().remove(&job_id);
+    app.emit("video-progress", VideoProgressInfo::new(job_id, &job)).unwrap();
+    res.inspect_err(|err| eprintln!("{:?}", err))
 }
 
+/// Cancels the job with `job_id`, or every running job if no id is given
 #[tauri::command]
-fn cancel_processing() {
-    unsafe {
-        mediatoascii::video::CANCEL_REQUESTED = true;
+fn cancel_processing(jobs: State<'_, VideoJobs>, job_id: Option<u64>) {
+    for (id, job) in jobs.running.lock().unwrap().iter() {
+        if job_id.is_none_or(|job_id| job_id == *id) {
+            job.cancel();
+        }
     }
 }
 
@@ -54,7 +82,8 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
-        .invoke_handler(tauri::generate_handler![process_video, video_progress, cancel_processing, file_exists])
+        .manage(VideoJobs::default())
+        .invoke_handler(tauri::generate_handler![process_video, cancel_processing, file_exists])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, available_parallelism};
+
+use mediatoascii::image::process_image;
+use mediatoascii::util::media::{MediaType, detect_media_type};
+use mediatoascii::util::stdio::PeekedStdin;
+use mediatoascii::video::process_video;
+
+use crate::{Cli, image_config, video_config};
+
+struct BatchInput {
+    path: PathBuf,
+    media_type: MediaType,
+    output: PathBuf,
+}
+
+struct BatchResult {
+    input: PathBuf,
+    status: BatchStatus,
+}
+
+enum BatchStatus {
+    Converted(PathBuf),
+    Failed(String),
+    /// Not a supported image or video
+    Skipped,
+}
+
+/// Converts every image and video in the directory, or matching the glob pattern, at `batch_path` to
+/// `output_dir`, with up to `cli.jobs` files converted in parallel.  Files that aren't images or videos are
+/// skipped.
+///
+/// A failure to convert one file doesn't stop the batch.  A summary of the conversions is printed at the
+/// end, and an error is returned if any of them failed.
+pub fn run_batch(cli: &Cli, batch_path: &str, output_dir: &str) -> Result<(), Box<dyn Error>> {
+    // Media types are detected up front so colliding output paths are caught before anything is written
+    let mut inputs: Vec<BatchInput> = vec![];
+    let mut unsupported: Vec<BatchResult> = vec![];
+    for path in collect_batch_inputs(batch_path)? {
+        match detect_media_type(&path.to_string_lossy()) {
+            Some(media_type) => {
+                let name = format_output_name(&cli.output_template, &path, inputs.len(), media_type);
+                inputs.push(BatchInput { output: Path::new(output_dir).join(name), path, media_type });
+            }
+            None => unsupported.push(BatchResult { input: path, status: BatchStatus::Skipped }),
+        }
+    }
+    if inputs.is_empty() {
+        return Err(format!("No images or videos found at {batch_path}").into());
+    }
+    check_duplicate_outputs(&inputs)?;
+    fs::create_dir_all(output_dir)?;
+
+    let jobs = cli
+        .jobs
+        .map(|jobs| jobs as usize)
+        .unwrap_or_else(|| available_parallelism().map(|n| n.get()).unwrap_or(1))
+        .min(inputs.len());
+    eprintln!("Converting {} files from {} to {} with {} jobs", inputs.len(), batch_path, output_dir, jobs);
+
+    let next_input = AtomicUsize::new(0);
+    // Skipped files are listed in the summary with the conversions
+    let results = Mutex::new(unsupported);
+    thread::scope(|s| {
+        for _ in 0..jobs {
+            s.spawn(|| {
+                loop {
+                    let index = next_input.fetch_add(1, Ordering::Relaxed);
+                    let Some(BatchInput { path: input, media_type, output }) = inputs.get(index) else {
+                        break;
+                    };
+
+                    eprintln!(
+                        "[{}/{}] Converting {} to {}",
+                        index + 1,
+                        inputs.len(),
+                        input.display(),
+                        output.display()
+                    );
+
+                    let status = match convert(cli, input, output, *media_type) {
+                        Ok(()) => BatchStatus::Converted(output.clone()),
+                        Err(e) => BatchStatus::Failed(e),
+                    };
+                    results.lock().unwrap().push(BatchResult { input: input.clone(), status });
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by(|a, b| a.input.cmp(&b.input));
+    let failures = results.iter().filter(|result| matches!(result.status, BatchStatus::Failed(_))).count();
+    let skipped = results.iter().filter(|result| matches!(result.status, BatchStatus::Skipped)).count();
+
+    eprintln!();
+    eprintln!("Batch conversion summary:");
+    for result in &results {
+        let input = result.input.display();
+        match &result.status {
+            BatchStatus::Converted(output) => eprintln!("  OK      {} -> {}", input, output.display()),
+            BatchStatus::Failed(error) => eprintln!("  FAILED  {}: {}", input, error),
+            BatchStatus::Skipped => eprintln!("  SKIPPED {}: not a supported image or video", input),
+        }
+    }
+    eprintln!("{} succeeded, {} failed, {} skipped", results.len() - failures - skipped, failures, skipped);
+
+    if failures > 0 {
+        return Err(format!("{failures} of {} files failed to convert", inputs.len()).into());
+    }
+    Ok(())
+}
+
+/// Lists the files of a batch, in order.  Directories are not traversed recursively.
+fn collect_batch_inputs(batch_path: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut inputs: Vec<PathBuf> = if Path::new(batch_path).is_dir() {
+        fs::read_dir(batch_path)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect()
+    } else {
+        glob::glob(batch_path)?.filter_map(Result::ok).filter(|path| path.is_file()).collect()
+    };
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// Fails if two inputs would be converted to the same output, such as files with the same name in different
+/// directories of a recursive glob, as one would overwrite the other
+fn check_duplicate_outputs(inputs: &[BatchInput]) -> Result<(), Box<dyn Error>> {
+    let mut outputs: HashMap<&Path, &Path> = HashMap::with_capacity(inputs.len());
+    for input in inputs {
+        if let Some(other) = outputs.insert(&input.output, &input.path) {
+            return Err(format!(
+                "{} and {} would both be converted to {}, add {{index}} to --output-template to tell them apart",
+                other.display(),
+                input.path.display(),
+                input.output.display()
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Extension substituted for `{ext}` in the output template
+fn default_extension(media_type: MediaType) -> &'static str {
+    match media_type {
+        MediaType::Image => "png",
+        MediaType::Video => "mp4",
+    }
+}
+
+fn format_output_name(template: &str, input: &Path, index: usize, media_type: MediaType) -> String {
+    let stem = input.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let name = input.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    template
+        .replace("{stem}", &stem)
+        .replace("{name}", &name)
+        .replace("{index}", &index.to_string())
+        .replace("{ext}", default_extension(media_type))
+}
+
+/// Converts a single file of the batch
+fn convert(cli: &Cli, input: &Path, output: &Path, media_type: MediaType) -> Result<(), String> {
+    let input = input.to_string_lossy().into_owned();
+    let output = Some(output.to_string_lossy().into_owned());
+
+    match media_type {
+        MediaType::Image => {
+            process_image(image_config(cli, input, output, PeekedStdin::default())).map_err(|e| e.to_string())
+        }
+        MediaType::Video => {
+            process_video(video_config(cli, input, output, PeekedStdin::default())).map_err(|e| e.to_string())
+        }
+    }
+}
@@ -1,8 +1,20 @@
+use std::path::Path;
+
 use clap::{ArgGroup, Parser};
+use tracing::level_filters::LevelFilter;
 
-use mediatoascii::image::{process_image, ImageConfigBuilder};
+use mediatoascii::image::{process_image, ImageConfig, ImageConfigBuilder};
 use mediatoascii::util::constants::MAGIC_HEIGHT_TO_WIDTH_RATIO;
-use mediatoascii::video::{process_video, VideoConfigBuilder};
+use mediatoascii::util::export::{is_export_path, ExportFormat};
+use mediatoascii::util::media::{detect_media_type, detect_stdin_media_type, MediaType};
+use mediatoascii::util::stdio::{is_stdio, PeekedStdin};
+use mediatoascii::video::{TimePosition, VideoConfig, VideoConfigBuilder, VideoJob, VideoResult};
+
+use crate::batch::run_batch;
+use crate::progress::CliProgress;
+
+mod batch;
+mod progress;
 
 /// Converts media (images and videos) to ascii, and displays output either as an output media file
 /// or in the terminal.
@@ -12,15 +24,55 @@ use mediatoascii::video::{process_video, VideoConfigBuilder};
     ArgGroup::new("input_path")
         .required(true)
         .multiple(false)
-        .args(&["image_path", "video_path"]),
+        .args(&["input", "image_path", "video_path", "batch_path", "capture"]),
 ))]
 struct Cli {
-    /// Input Image file.  One of image_path, or video_path must be populated.
+    /// Input image or video, detected from its contents.  Use image_path or video_path instead to
+    /// override the detection.  Use `-` to read the input from stdin.  URLs of network streams, such as
+    /// http(s) videos, HLS playlists and RTSP streams, are read as videos.
+    #[clap(value_parser)]
+    input: Option<String>,
+    /// Input Image file.  One of input, image_path, video_path, or batch_path must be populated.
     #[clap(long, value_parser)]
     image_path: Option<String>,
-    /// Input Video file.  One of image_path, or video_path must be populated.
+    /// Input Video file.  One of input, image_path, video_path, or batch_path must be populated.
+    /// Image sequences are also supported, as a directory of images or a printf-style pattern such as
+    /// `frame_%04d.png`.
     #[clap(long, value_parser)]
     video_path: Option<String>,
+    /// Batch convert every image and video in a directory, or every file matching a glob pattern such
+    /// as `media/**/*.jpg`.  Each file is detected as an image or video from its contents, and converted
+    /// to `output_dir`.  Other files are skipped.
+    #[clap(long, value_parser, requires = "output_dir")]
+    batch_path: Option<String>,
+    /// Play live ascii from a capture device in the terminal, such as a webcam.  Either a device index
+    /// like `0`, or a device path like `/dev/video0` on Linux.  A video file can also be given as a
+    /// stand-in device.  Capture can be streamed to a live output with output_file_path.
+    #[clap(long, value_parser)]
+    capture: Option<String>,
+    /// Output directory for batch conversions
+    #[clap(long, value_parser)]
+    output_dir: Option<String>,
+    /// Filename template of batch conversion outputs.  `{stem}` and `{name}` are replaced with the input
+    /// file's stem and name, `{index}` with its position in the batch, and `{ext}` with `png` for images
+    /// or `mp4` for videos.
+    #[clap(long, default_value = "{stem}.{ext}", value_parser = parse_output_path)]
+    output_template: String,
+    /// Maximum number of files converted in parallel during batch conversions. [default: number of
+    /// logical CPU cores]
+    #[clap(short, long, value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: Option<u16>,
+    /// Frame rate of image sequence inputs
+    #[clap(long, default_value_t = 25.0, value_parser)]
+    fps: f64,
+    /// ffmpeg container format of a video read from stdin, such as `matroska` or `mpegts`, if it can't be
+    /// detected from its contents
+    #[clap(long, value_parser)]
+    input_format: Option<String>,
+    /// ffmpeg container format of a video written to stdout.  Defaults to a fragmented `mp4`, and
+    /// `matroska` is also supported.
+    #[clap(long, value_parser)]
+    output_format: Option<String>,
     /// Multiplier to scale down input dimensions by when converting to ASCII.  For large frames,
     /// recommended to scale down more so output file size is more reasonable.  Affects output quality.
     /// Note: the output dimensions will also depend on the `font-size` setting.
@@ -53,19 +105,64 @@ struct Cli {
     /// max_fps=10 for smoother visuals.
     #[clap(long, value_parser)]
     max_fps: Option<u64>,
+    /// Start videos at this position, as a timestamp such as `90`, `1:30` or `00:01:30.5`, or a frame number
+    /// such as `150f`
+    #[clap(long, value_parser)]
+    start: Option<TimePosition>,
+    /// Stop videos before this position, as a timestamp or a frame number like `start`
+    #[clap(long, value_parser, conflicts_with = "duration")]
+    end: Option<TimePosition>,
+    /// Length of video to convert or play from `start`, as a timestamp such as `5` or `0:05`, or a number of
+    /// frames such as `120f`
+    #[clap(long, value_parser)]
+    duration: Option<TimePosition>,
+    /// Only convert every nth frame of videos, for quick previews.  Output videos play at the input FPS
+    /// divided by this, so they keep the input's duration.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..), default_value_t = 1)]
+    every_nth_frame: u64,
     /// Bitrate for output video file when using ffmpeg
     #[clap(long, value_parser)]
     bitrate: Option<u64>,
     /// For images, if output_file_path is specified, will save the ascii text as-is to the output
     /// rather than an image file.
+    /// For videos, saves the ascii frames as a text video, which can be edited and rendered back into
+    /// a video (.mp4 or .gif) by passing it as the video-path.  If the output path has no extension,
+    /// the frames are saved to a directory with one file per frame.
     #[clap(long, action)]
     as_text: bool,
+    /// For videos, saves each ascii frame as a numbered PNG image to output_file_path, which is either a
+    /// directory or a printf-style pattern such as `ascii_%04d.png`.
+    #[clap(long, action)]
+    as_images: bool,
     /// Output file path.  If omitted, output will be written to console.
     /// Supports most image formats, and .mp4 video outputs.
+    /// Videos written to an RTMP, SRT or UDP URL, or to an HLS `.m3u8` playlist, are streamed live in real
+    /// time as the input is read.
+    /// Use `-` to write the output to stdout, which is a PNG for images, or a video streamed in the
+    /// `output_format` container for videos.  With `as_text`, the ascii text or text video is written to
+    /// stdout instead.
+    /// Images written to a `.ans` file are saved as ANSI art with 24-bit colors, and videos written to a
+    /// `.cast` file are saved as an asciinema recording.
+    /// Outputs to a `.json` or `.msgpack` file are saved as a structured export of the ascii, with
+    /// per-frame timestamps for videos.
     /// Images will be resized to fit the ascii text.  Videos will honor the aspect ratio of the
     /// input, but resolution will be scaled differently approximately to `(height|width) / scale_down * font_size`.
-    #[clap(short, long, value_parser)]
+    #[clap(short, long, value_parser = parse_output_path)]
     output_file_path: Option<String>,
+    /// Format of structured exports, `json` or `msgpack`.  Defaults to the output file's extension, and
+    /// writes a structured export to stdout when the output path is `-`.
+    #[clap(long, value_parser = parse_export_format)]
+    export_format: Option<ExportFormat>,
+    /// Include the source color of each ascii cell in .json/.msgpack outputs
+    #[clap(long, action)]
+    export_colors: bool,
+    /// Include the source luminance of each ascii cell in .json/.msgpack outputs
+    #[clap(long, action)]
+    export_luminance: bool,
+    /// Fit ascii in the console to the terminal size, overriding scale_down while preserving the aspect
+    /// ratio.  Videos are re-fit when the terminal is resized.
+    #[clap(long, action)]
+    fit_terminal: bool,
     /// Use the max_fps setting for video file outputs.
     #[clap(long, action)]
     use_max_fps_for_output_video: bool,
@@ -78,6 +175,12 @@ struct Cli {
     /// Use OpenCV for video processing instead of ffmpeg
     #[clap(long, action)]
     use_opencv: bool,
+    /// Log more details to stderr.  Use -vv to also log every frame.
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Log less to stderr, and hide progress bars.  Use -qq to only log errors, and -qqq to log nothing.
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    quiet: u8,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -106,60 +209,156 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let cli = Cli::parse();
     // Note: Rust plugin can expand procedural macros using https://github.com/intellij-rust/intellij-rust/issues/6908
+    init_logging(&cli);
 
-    if let Some(image_path) = cli.image_path {
-        let mut config_builder = ImageConfigBuilder::default();
-        config_builder
-            .image_path(image_path)
-            .scale_down(cli.scale_down)
-            .font_size(cli.font_size)
-            .height_sample_scale(cli.height_sample_scale)
-            .invert(cli.invert)
-            .overwrite(cli.overwrite);
-
-        if let Some(output_path) = cli.output_file_path {
-            if cli.as_text {
-                config_builder.output_file_path(Some(output_path));
-            } else {
-                config_builder.output_image_path(Some(output_path));
-            }
+    if let Some(batch_path) = cli.batch_path.as_ref() {
+        run_batch(&cli, batch_path, cli.output_dir.as_ref().unwrap())?;
+    } else if let Some(input) = cli.input.clone() {
+        let (media_type, stdin) = if is_stdio(&input) {
+            detect_stdin_media_type()?
+        } else {
+            (detect_media_type(&input), PeekedStdin::default())
+        };
+        let output = cli.output_file_path.clone();
+        match media_type {
+            Some(MediaType::Image) => process_image(image_config(&cli, input, output, stdin))?,
+            Some(MediaType::Video) => run_video(&cli, video_config(&cli, input, output, stdin))?,
+            None => return Err(format!("Unsupported media: {input} is not a supported image or video").into()),
         }
+    } else if let Some(image_path) = cli.image_path.clone() {
+        process_image(image_config(&cli, image_path, cli.output_file_path.clone(), PeekedStdin::default()))?;
+    } else if let Some(video_path) = cli.video_path.clone() {
+        run_video(&cli, video_config(&cli, video_path, cli.output_file_path.clone(), PeekedStdin::default()))?;
+    } else if cli.capture.is_some() {
+        run_video(&cli, video_config(&cli, String::new(), cli.output_file_path.clone(), PeekedStdin::default()))?;
+    } else {
+        panic!("Either an input, image-path, video-path, batch-path or capture must be provided!");
+    }
 
-        let config = config_builder.build().unwrap();
-        process_image(config);
-    } else if let Some(video_path) = cli.video_path {
-        let mut config_builder = VideoConfigBuilder::default();
-        config_builder
-            .video_path(video_path)
-            .scale_down(cli.scale_down)
-            .font_size(cli.font_size)
-            .invert(cli.invert)
-            .overwrite(cli.overwrite)
-            .use_max_fps_for_output_video(cli.use_max_fps_for_output_video)
-            .use_opencv(cli.use_opencv);
-
-        if let Some(bitrate) = cli.bitrate {
-            config_builder.bitrate(Some(bitrate));
-        }
-        //.num_threads(cli.num_threads.unwrap_or_else(|| available_parallelism().unwrap().get() as u8));
+    Ok(())
+}
+
+/// Logs to stderr, at a level set by the verbose and quiet flags
+fn init_logging(cli: &Cli) {
+    let level = match i16::from(cli.verbose) - i16::from(cli.quiet) {
+        ..=-3 => LevelFilter::OFF,
+        -2 => LevelFilter::ERROR,
+        -1 => LevelFilter::WARN,
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    tracing_subscriber::fmt().with_max_level(level).with_writer(std::io::stderr).init();
+}
+
+/// Processes a video, drawing its progress to stderr unless quiet
+fn run_video(cli: &Cli, config: VideoConfig) -> VideoResult<()> {
+    let job = VideoJob::new();
+    if cli.quiet > 0 { job.run(config) } else { job.with_observer(CliProgress::default()).run(config) }
+}
+
+/// Config of an image conversion.  `stdin` holds the bytes of piped input that were read to detect its type.
+fn image_config(cli: &Cli, image_path: String, output_path: Option<String>, stdin: PeekedStdin) -> ImageConfig {
+    let mut config_builder = ImageConfigBuilder::default();
+    config_builder
+        .image_path(image_path)
+        .stdin(stdin)
+        .scale_down(cli.scale_down)
+        .font_size(cli.font_size)
+        .height_sample_scale(cli.height_sample_scale)
+        .invert(cli.invert)
+        .export_colors(cli.export_colors)
+        .export_luminance(cli.export_luminance)
+        .fit_terminal(cli.fit_terminal)
+        .overwrite(cli.overwrite);
 
-        if let Some(max_fps) = cli.max_fps {
-            config_builder.max_fps(max_fps);
+    if let Some(output_path) = output_path {
+        if cli.as_text {
+            config_builder.output_file_path(Some(output_path));
+        } else if cli.export_format.is_some() || is_export_path(&output_path) {
+            config_builder.output_export_path(Some(output_path)).export_format(cli.export_format);
+        } else if has_extension(&output_path, "ans") {
+            config_builder.output_ansi_path(Some(output_path));
+        } else {
+            config_builder.output_image_path(Some(output_path));
         }
+    }
+
+    config_builder.build().unwrap()
+}
+
+/// Config of a video conversion, with `stdin` like [image_config]
+fn video_config(cli: &Cli, video_path: String, output_path: Option<String>, stdin: PeekedStdin) -> VideoConfig {
+    let mut config_builder = VideoConfigBuilder::default();
+    config_builder
+        .video_path(video_path)
+        .stdin(stdin)
+        .input_format(cli.input_format.clone())
+        .output_format(cli.output_format.clone())
+        .capture_device(cli.capture.clone())
+        .sequence_fps(cli.fps)
+        .start(cli.start)
+        .end(cli.end)
+        .duration(cli.duration)
+        .every_nth_frame(cli.every_nth_frame)
+        .scale_down(cli.scale_down)
+        .font_size(cli.font_size)
+        .invert(cli.invert)
+        .export_colors(cli.export_colors)
+        .export_luminance(cli.export_luminance)
+        .overwrite(cli.overwrite)
+        .fit_terminal(cli.fit_terminal)
+        .use_max_fps_for_output_video(cli.use_max_fps_for_output_video)
+        .use_opencv(cli.use_opencv);
 
-        if let Some(output_path) = cli.output_file_path {
+    if let Some(bitrate) = cli.bitrate {
+        config_builder.bitrate(Some(bitrate));
+    }
+    //.num_threads(cli.num_threads.unwrap_or_else(|| available_parallelism().unwrap().get() as u8));
+
+    if let Some(max_fps) = cli.max_fps {
+        config_builder.max_fps(max_fps);
+    }
+
+    if let Some(output_path) = output_path {
+        if cli.as_text {
+            config_builder
+                .text_frames_as_directory(!is_stdio(&output_path) && Path::new(&output_path).extension().is_none());
+            config_builder.output_text_path(Some(output_path));
+        } else if cli.as_images {
+            config_builder.output_frames_path(Some(output_path));
+        } else if cli.export_format.is_some() || is_export_path(&output_path) {
+            config_builder.output_export_path(Some(output_path)).export_format(cli.export_format);
+        } else if has_extension(&output_path, "cast") {
+            config_builder.output_cast_path(Some(output_path));
+        } else {
             config_builder.output_video_path(Some(output_path));
         }
+    }
 
-        if let Some(rotate) = cli.rotate {
-            config_builder.rotate(rotate);
-        }
+    if let Some(rotate) = cli.rotate {
+        config_builder.rotate(rotate);
+    }
 
-        let config = config_builder.build().unwrap();
-        process_video(config)?;
-    } else {
-        panic!("Either image-path or video-path must be provided!");
+    config_builder.build().unwrap()
+}
+
+fn parse_export_format(format: &str) -> Result<ExportFormat, String> {
+    let format: ExportFormat = format.parse()?;
+    if !format.is_supported() {
+        return Err("MessagePack exports require mediatoascii to be built with the `msgpack` feature".to_string());
     }
+    Ok(format)
+}
 
-    Ok(())
+/// Rejects outputs with the extension of an export format this build can't write
+fn parse_output_path(path: &str) -> Result<String, String> {
+    if ExportFormat::from_path(path).is_some_and(|format| !format.is_supported()) {
+        return Err(format!("{path} is a MessagePack export, which requires the `msgpack` feature"));
+    }
+    Ok(path.to_string())
+}
+
+fn has_extension(path: &str, extension: &str) -> bool {
+    Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
 }
@@ -0,0 +1,54 @@
+use std::sync::Mutex;
+
+use indicatif::{ProgressBar, ProgressStyle};
+use mediatoascii::util::progress::{ProgressEvent, ProgressObserver, Stage};
+
+const BAR_TEMPLATE: &str = "{msg:>10} [{elapsed_precise}] {wide_bar} {pos}/{len} (ETA {eta})";
+/// Streams of unknown length have no end to measure progress against
+const SPINNER_TEMPLATE: &str = "{msg:>10} [{elapsed_precise}] {spinner} {pos} frames";
+
+/// Draws the progress of a video to stderr, with a progress bar for each stage
+#[derive(Default)]
+pub struct CliProgress {
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl ProgressObserver for CliProgress {
+    fn on_progress(&self, event: &ProgressEvent) {
+        let mut bar = self.bar.lock().unwrap();
+        match event {
+            // Playback draws to the terminal itself
+            ProgressEvent::StageChanged { stage: Stage::Playing, .. } => *bar = None,
+            ProgressEvent::StageChanged { stage, total_frames } => {
+                if let Some(bar) = bar.take() {
+                    bar.finish();
+                }
+                let (new_bar, template) = match total_frames {
+                    Some(total_frames) => (ProgressBar::new(*total_frames), BAR_TEMPLATE),
+                    None => (ProgressBar::no_length(), SPINNER_TEMPLATE),
+                };
+                new_bar.set_style(ProgressStyle::with_template(template).unwrap());
+                new_bar.set_message(format!("{stage:?}"));
+                *bar = Some(new_bar);
+            }
+            ProgressEvent::FrameRead { .. }
+            | ProgressEvent::FrameEncoded { .. }
+            | ProgressEvent::FrameWritten { .. } => {
+                if let Some(bar) = bar.as_ref() {
+                    bar.inc(1);
+                }
+            }
+            ProgressEvent::Finished => {
+                if let Some(bar) = bar.take() {
+                    bar.finish();
+                }
+            }
+            ProgressEvent::Error { .. } => {
+                if let Some(bar) = bar.take() {
+                    bar.abandon();
+                }
+            }
+            ProgressEvent::ProbeFinished { .. } | ProgressEvent::Eta { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use std::env;
+use std::path::Path;
+
+/// Set to regenerate the C header checked in at `include/mediatoascii.h`, after changing the exported functions
+const UPDATE_HEADER_VAR: &str = "MEDIATOASCII_FFI_UPDATE_HEADER";
+
+/// Generates the C header for the exported functions in the build's output directory, and updates the checked in
+/// header when [UPDATE_HEADER_VAR] is set
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed={UPDATE_HEADER_VAR}");
+
+    let bindings = cbindgen::generate(&crate_dir).expect("Could not generate C header");
+    bindings.write_to_file(Path::new(&env::var("OUT_DIR").unwrap()).join("mediatoascii.h"));
+    if env::var_os(UPDATE_HEADER_VAR).is_some() {
+        bindings.write_to_file(Path::new(&crate_dir).join("include").join("mediatoascii.h"));
+    }
+}
@@ -0,0 +1,120 @@
+#ifndef MEDIATOASCII_H
+#define MEDIATOASCII_H
+
+/* Generated by cbindgen from mediatoascii-ffi, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Layout of the pixels of an image buffer
+typedef enum MtaPixelFormat {
+  MTA_PIXEL_FORMAT_RGB8 = 0,
+  MTA_PIXEL_FORMAT_RGBA8 = 1,
+} MtaPixelFormat;
+
+// Result of functions that don't return an object
+typedef enum MtaStatus {
+  MTA_STATUS_OK = 0,
+  MTA_STATUS_ERROR = 1,
+  // The video job was cancelled before it finished
+  MTA_STATUS_CANCELLED = 2,
+} MtaStatus;
+
+// Settings for image and video conversions.  Defaults are the same as [ImageConfig] and [VideoConfig].
+typedef struct MtaOptions MtaOptions;
+
+// Video conversion running on a background thread
+typedef struct MtaVideoJob MtaVideoJob;
+
+// Image rendered from ascii, as 8-bit RGB pixels row by row, without padding
+typedef struct MtaImage {
+  uint8_t *data;
+  // Length of `data` in bytes, `width * height * 3`
+  uintptr_t len;
+  uint32_t width;
+  uint32_t height;
+} MtaImage;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message of the last error on the calling thread, or `NULL` if the last call that can fail succeeded.  The
+// message is owned by the library, and stays valid until the next call that can fail on the same thread.
+const char *mta_last_error(void);
+
+// Frees a string returned by the library
+void mta_string_free(char *s);
+
+// Converts an image of `width` x `height` pixels, with rows starting `stride` bytes apart, to ascii text with
+// each row ending in a newline.  The text is freed with `mta_string_free`.
+char *mta_image_to_ascii(const uint8_t *pixels,
+                         uint32_t width,
+                         uint32_t height,
+                         uintptr_t stride,
+                         enum MtaPixelFormat format,
+                         const struct MtaOptions *options);
+
+// Renders ascii text, such as returned by `mta_image_to_ascii`, to an RGB image sized to fit the text at the
+// options' font size.  The image is freed with `mta_image_free`.
+struct MtaImage *mta_render_ascii(const char *text,
+                                  const struct MtaOptions *options);
+
+void mta_image_free(struct MtaImage *image);
+
+// Creates default options, freed with `mta_options_free`
+struct MtaOptions *mta_options_new(void);
+
+void mta_options_free(struct MtaOptions *options);
+
+// Source pixels per ascii column
+void mta_options_set_scale_down(struct MtaOptions *options, float scale_down);
+
+// Font size of rendered ascii
+void mta_options_set_font_size(struct MtaOptions *options, float font_size);
+
+// Source pixels per ascii row, relative to the scale down, as characters are taller than they are wide
+void mta_options_set_height_sample_scale(struct MtaOptions *options,
+                                         float height_sample_scale);
+
+// Invert the greyscale ramp, and render dark text on a light background
+void mta_options_set_invert(struct MtaOptions *options, bool invert);
+
+// Overwrite existing output files
+void mta_options_set_overwrite(struct MtaOptions *options, bool overwrite);
+
+// Maximum frames per second of video outputs
+void mta_options_set_max_fps(struct MtaOptions *options, uint64_t max_fps);
+
+// Read and write videos with OpenCV instead of ffmpeg
+void mta_options_set_use_opencv(struct MtaOptions *options, bool use_opencv);
+
+// Starts converting the video at `input_path` to an ascii video at `output_path` on a background thread.
+// The job is freed with `mta_video_job_free`.
+struct MtaVideoJob *mta_video_job_start(const char *input_path,
+                                        const char *output_path,
+                                        const struct MtaOptions *options);
+
+// Overall progress of the job, from 0 to 1
+float mta_video_job_progress(const struct MtaVideoJob *job);
+
+// Whether the job finished, so `mta_video_job_wait` returns without blocking
+bool mta_video_job_is_finished(const struct MtaVideoJob *job);
+
+// Requests cancellation.  The job stops at the next frame, and `mta_video_job_wait` returns
+// `MTA_STATUS_CANCELLED`.
+void mta_video_job_cancel(const struct MtaVideoJob *job);
+
+// Blocks until the job finishes, returning whether it succeeded
+enum MtaStatus mta_video_job_wait(struct MtaVideoJob *job);
+
+// Frees the job, cancelling it and waiting for it to stop if it is still running
+void mta_video_job_free(struct MtaVideoJob *job);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MEDIATOASCII_H */
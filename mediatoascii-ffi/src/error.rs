@@ -0,0 +1,82 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+/// Result of functions that don't return an object
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MtaStatus {
+    Ok = 0,
+    Error = 1,
+    /// The video job was cancelled before it finished
+    Cancelled = 2,
+}
+
+thread_local! {
+    /// Message of the last error on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn set_last_error(message: impl Into<String>) {
+    // Only paths passed in could contain NUL, which C strings can't
+    let message = CString::new(message.into().replace('\0', "")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+pub(crate) fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Runs `f`, keeping its error, or the message of a panic, as the last error rather than unwinding into C.
+/// The last error is cleared if `f` succeeds.
+pub(crate) fn guard<T>(f: impl FnOnce() -> Result<T, String>) -> Option<T> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => {
+            clear_last_error();
+            Some(value)
+        }
+        Ok(Err(message)) => {
+            set_last_error(message);
+            None
+        }
+        Err(panic) => {
+            set_last_error(panic_message(panic.as_ref()));
+            None
+        }
+    }
+}
+
+pub(crate) fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_string()
+    }
+}
+
+/// Borrows the C string argument `name` as UTF-8
+pub(crate) unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err(format!("{name} is NULL"));
+    }
+    unsafe { CStr::from_ptr(s) }.to_str().map_err(|_| format!("{name} is not valid UTF-8"))
+}
+
+/// Message of the last error on the calling thread, or `NULL` if the last call that can fail succeeded.  The
+/// message is owned by the library, and stays valid until the next call that can fail on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn mta_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Frees a string returned by the library
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
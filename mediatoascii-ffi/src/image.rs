@@ -0,0 +1,194 @@
+use std::ffi::{CString, c_char};
+use std::{ptr, slice};
+
+use image::{DynamicImage, RgbImage, RgbaImage};
+use mediatoascii::ascii::{AsciiFrame, RenderOptions, render_ascii};
+use mediatoascii::image::convert_dynamic_image_to_ascii;
+
+use crate::error::{guard, str_arg};
+use crate::options::{MtaOptions, image_config};
+
+/// Layout of the pixels of an image buffer
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MtaPixelFormat {
+    Rgb8 = 0,
+    Rgba8 = 1,
+}
+
+impl MtaPixelFormat {
+    fn bytes_per_pixel(self) -> usize {
+        match self {
+            MtaPixelFormat::Rgb8 => 3,
+            MtaPixelFormat::Rgba8 => 4,
+        }
+    }
+}
+
+/// Image rendered from ascii, as 8-bit RGB pixels row by row, without padding
+#[repr(C)]
+pub struct MtaImage {
+    pub data: *mut u8,
+    /// Length of `data` in bytes, `width * height * 3`
+    pub len: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Copies the image in `pixels` without the padding between rows
+unsafe fn read_image(
+    pixels: *const u8,
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: MtaPixelFormat,
+) -> Result<DynamicImage, String> {
+    if pixels.is_null() {
+        return Err("pixels is NULL".to_string());
+    }
+    let too_large = || format!("Image of {width}x{height} pixels with a stride of {stride} bytes is too large");
+    let row_len = (width as usize).checked_mul(format.bytes_per_pixel()).ok_or_else(too_large)?;
+    if stride < row_len {
+        return Err(format!("Stride of {stride} bytes is shorter than a row of {row_len} bytes"));
+    }
+
+    let len = match (height as usize).checked_sub(1) {
+        None => 0,
+        Some(rows) => stride
+            .checked_mul(rows)
+            .and_then(|len| len.checked_add(row_len))
+            .filter(|len| *len <= isize::MAX as usize)
+            .ok_or_else(too_large)?,
+    };
+    let data = unsafe { slice::from_raw_parts(pixels, len) };
+    // Stride is only 0 for images without any pixels, which have no rows to copy
+    let buffer: Vec<u8> = data.chunks(stride.max(1)).flat_map(|row| &row[..row_len]).copied().collect();
+    Ok(match format {
+        MtaPixelFormat::Rgb8 => DynamicImage::ImageRgb8(RgbImage::from_raw(width, height, buffer).unwrap()),
+        MtaPixelFormat::Rgba8 => DynamicImage::ImageRgba8(RgbaImage::from_raw(width, height, buffer).unwrap()),
+    })
+}
+
+/// Converts an image of `width` x `height` pixels, with rows starting `stride` bytes apart, to ascii text with
+/// each row ending in a newline.  The text is freed with `mta_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_image_to_ascii(
+    pixels: *const u8,
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: MtaPixelFormat,
+    options: *const MtaOptions,
+) -> *mut c_char {
+    guard(|| {
+        let img = unsafe { read_image(pixels, width, height, stride, format) }?;
+        let ascii = convert_dynamic_image_to_ascii(&img, &unsafe { image_config(options) }?);
+        Ok(CString::new(ascii.to_string()).unwrap().into_raw())
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Renders ascii text, such as returned by `mta_image_to_ascii`, to an RGB image sized to fit the text at the
+/// options' font size.  The image is freed with `mta_image_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_render_ascii(text: *const c_char, options: *const MtaOptions) -> *mut MtaImage {
+    guard(|| {
+        let text = unsafe { str_arg(text, "text") }?;
+        let render_options = RenderOptions::from(&unsafe { image_config(options) }?);
+        let img = render_ascii(&AsciiFrame::from_text(text), &render_options);
+        let (width, height) = img.dimensions();
+        let data = Box::into_raw(img.into_raw().into_boxed_slice());
+        Ok(Box::into_raw(Box::new(MtaImage { data: data as *mut u8, len: data.len(), width, height })))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_image_free(image: *mut MtaImage) {
+    if !image.is_null() {
+        let image = unsafe { Box::from_raw(image) };
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(image.data, image.len)) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+    use crate::error::mta_last_error;
+    use crate::options::{mta_options_free, mta_options_new, mta_options_set_height_sample_scale};
+
+    fn last_error() -> Option<String> {
+        let message = mta_last_error();
+        (!message.is_null()).then(|| unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_string())
+    }
+
+    /// 4x4 RGB image with 4 bytes of padding after each row, white in the top half and black in the bottom
+    fn padded_image() -> Vec<u8> {
+        let mut pixels = vec![0; 16 * 4];
+        pixels[..32].chunks_mut(16).for_each(|row| row[..12].fill(255));
+        pixels
+    }
+
+    #[test]
+    fn reads_images_without_padding() {
+        let pixels = padded_image();
+        let img = unsafe { read_image(pixels.as_ptr(), 4, 4, 16, MtaPixelFormat::Rgb8) }.unwrap().into_rgb8();
+        assert_eq!(img.dimensions(), (4, 4));
+        assert!(img.rows().take(2).flatten().all(|pixel| pixel.0 == [255; 3]));
+        assert!(img.rows().skip(2).flatten().all(|pixel| pixel.0 == [0; 3]));
+
+        let empty = unsafe { read_image(pixels.as_ptr(), 0, 0, 0, MtaPixelFormat::Rgba8) }.unwrap();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+    }
+
+    #[test]
+    fn rejects_null_pixels() {
+        let ascii = unsafe { mta_image_to_ascii(ptr::null(), 4, 4, 16, MtaPixelFormat::Rgb8, ptr::null()) };
+        assert!(ascii.is_null());
+        assert_eq!(last_error().as_deref(), Some("pixels is NULL"));
+
+        assert!(unsafe { mta_render_ascii(ptr::null(), ptr::null()) }.is_null());
+        assert_eq!(last_error().as_deref(), Some("text is NULL"));
+    }
+
+    #[test]
+    fn rejects_bad_strides() {
+        let pixels = padded_image();
+        let ascii = unsafe { mta_image_to_ascii(pixels.as_ptr(), 4, 4, 8, MtaPixelFormat::Rgb8, ptr::null()) };
+        assert!(ascii.is_null());
+        assert_eq!(last_error().as_deref(), Some("Stride of 8 bytes is shorter than a row of 12 bytes"));
+
+        let ascii = unsafe {
+            mta_image_to_ascii(pixels.as_ptr(), u32::MAX, u32::MAX, usize::MAX, MtaPixelFormat::Rgba8, ptr::null())
+        };
+        assert!(ascii.is_null());
+        assert!(last_error().is_some_and(|message| message.ends_with("is too large")));
+    }
+
+    #[test]
+    fn converts_and_renders_ascii() {
+        let options = mta_options_new();
+        unsafe { mta_options_set_height_sample_scale(options, 2.0) };
+        let pixels = padded_image();
+        // Errors are cleared by the next call that succeeds
+        assert!(unsafe { mta_image_to_ascii(ptr::null(), 4, 4, 16, MtaPixelFormat::Rgb8, options) }.is_null());
+
+        let ascii = unsafe { mta_image_to_ascii(pixels.as_ptr(), 4, 4, 16, MtaPixelFormat::Rgb8, options) };
+        assert!(!ascii.is_null());
+        assert_eq!(last_error(), None);
+        assert_eq!(unsafe { CStr::from_ptr(ascii) }.to_str().unwrap(), "@@@@\n    \n");
+
+        let image = unsafe { mta_render_ascii(ascii, options) };
+        let rendered = unsafe { image.as_ref() }.unwrap();
+        // 4 columns of 12px characters, half as wide as they are tall, by 2 rows
+        assert_eq!((rendered.width, rendered.height, rendered.len), (24, 24, 24 * 24 * 3));
+
+        unsafe {
+            mta_image_free(image);
+            crate::error::mta_string_free(ascii);
+            mta_options_free(options);
+        }
+    }
+}
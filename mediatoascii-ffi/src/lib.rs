@@ -0,0 +1,23 @@
+//! C ABI for mediatoascii, built as a shared library.  The header for it is generated by cbindgen at
+//! `include/mediatoascii.h` when the crate is built.
+//!
+//! Functions that fail return `NULL`, or a status other than `MTA_STATUS_OK`, and the reason can be read with
+//! `mta_last_error`.  Everything returned is owned by the caller, and freed with the matching `_free` function.
+//!
+//! # Safety
+//!
+//! Pointer arguments must be `NULL`, or point to valid values of their type, and objects created by this library
+//! must only be freed once, by this library.  `NULL` options are the same as the default options.
+
+// The safety requirements are the same for every function, see above
+#![allow(clippy::missing_safety_doc)]
+
+mod error;
+mod image;
+mod options;
+mod video;
+
+pub use error::*;
+pub use image::*;
+pub use options::*;
+pub use video::*;
@@ -0,0 +1,110 @@
+use mediatoascii::image::{ImageConfig, ImageConfigBuilder};
+use mediatoascii::video::{VideoConfig, VideoConfigBuilder};
+
+/// Settings for image and video conversions.  Defaults are the same as [ImageConfig] and [VideoConfig].
+#[derive(Clone, Default)]
+pub struct MtaOptions {
+    image: ImageConfigBuilder,
+    video: VideoConfigBuilder,
+}
+
+impl MtaOptions {
+    pub(crate) fn image_config(&self) -> Result<ImageConfig, String> {
+        self.image.build().map_err(|e| format!("Invalid image options: {e}"))
+    }
+
+    pub(crate) fn video_config(&self, video_path: &str, output_video_path: &str) -> Result<VideoConfig, String> {
+        let mut video = self.video.clone();
+        video.video_path(video_path.to_string()).output_video_path(Some(output_video_path.to_string()));
+        video.build().map_err(|e| format!("Invalid video options: {e}"))
+    }
+}
+
+/// Config of `options`, or the default config if `options` is `NULL`
+pub(crate) unsafe fn image_config(options: *const MtaOptions) -> Result<ImageConfig, String> {
+    unsafe { options.as_ref() }.map_or_else(|| Ok(ImageConfig::default()), MtaOptions::image_config)
+}
+
+pub(crate) unsafe fn video_config(
+    options: *const MtaOptions,
+    video_path: &str,
+    output_video_path: &str,
+) -> Result<VideoConfig, String> {
+    unsafe { options.as_ref() }
+        .map_or_else(MtaOptions::default, MtaOptions::clone)
+        .video_config(video_path, output_video_path)
+}
+
+/// Creates default options, freed with `mta_options_free`
+#[unsafe(no_mangle)]
+pub extern "C" fn mta_options_new() -> *mut MtaOptions {
+    Box::into_raw(Box::default())
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_options_free(options: *mut MtaOptions) {
+    if !options.is_null() {
+        drop(unsafe { Box::from_raw(options) });
+    }
+}
+
+/// Source pixels per ascii column
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_options_set_scale_down(options: *mut MtaOptions, scale_down: f32) {
+    if let Some(options) = unsafe { options.as_mut() } {
+        options.image.scale_down(scale_down);
+        options.video.scale_down(scale_down);
+    }
+}
+
+/// Font size of rendered ascii
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_options_set_font_size(options: *mut MtaOptions, font_size: f32) {
+    if let Some(options) = unsafe { options.as_mut() } {
+        options.image.font_size(font_size);
+        options.video.font_size(font_size);
+    }
+}
+
+/// Source pixels per ascii row, relative to the scale down, as characters are taller than they are wide
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_options_set_height_sample_scale(options: *mut MtaOptions, height_sample_scale: f32) {
+    if let Some(options) = unsafe { options.as_mut() } {
+        options.image.height_sample_scale(height_sample_scale);
+        options.video.height_sample_scale(height_sample_scale);
+    }
+}
+
+/// Invert the greyscale ramp, and render dark text on a light background
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_options_set_invert(options: *mut MtaOptions, invert: bool) {
+    if let Some(options) = unsafe { options.as_mut() } {
+        options.image.invert(invert);
+        options.video.invert(invert);
+    }
+}
+
+/// Overwrite existing output files
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_options_set_overwrite(options: *mut MtaOptions, overwrite: bool) {
+    if let Some(options) = unsafe { options.as_mut() } {
+        options.image.overwrite(overwrite);
+        options.video.overwrite(overwrite);
+    }
+}
+
+/// Maximum frames per second of video outputs
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_options_set_max_fps(options: *mut MtaOptions, max_fps: u64) {
+    if let Some(options) = unsafe { options.as_mut() } {
+        options.video.max_fps(max_fps);
+    }
+}
+
+/// Read and write videos with OpenCV instead of ffmpeg
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_options_set_use_opencv(options: *mut MtaOptions, use_opencv: bool) {
+    if let Some(options) = unsafe { options.as_mut() } {
+        options.video.use_opencv(use_opencv);
+    }
+}
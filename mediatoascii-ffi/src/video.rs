@@ -0,0 +1,110 @@
+use std::ffi::c_char;
+use std::ptr;
+use std::thread::{self, JoinHandle};
+
+use mediatoascii::video::{VideoJob, VideoResult};
+
+use crate::error::{MtaStatus, clear_last_error, guard, panic_message, set_last_error, str_arg};
+use crate::options::{MtaOptions, video_config};
+
+/// Video conversion running on a background thread
+pub struct MtaVideoJob {
+    job: VideoJob,
+    /// Taken once the job is waited on
+    handle: Option<JoinHandle<VideoResult<()>>>,
+    status: MtaStatus,
+}
+
+impl MtaVideoJob {
+    /// Waits for the job to finish, keeping its status to return to later waits
+    fn wait(&mut self) -> MtaStatus {
+        if let Some(handle) = self.handle.take() {
+            self.status = match handle.join() {
+                Ok(Ok(())) => MtaStatus::Ok,
+                Ok(Err(_)) if self.job.is_cancelled() => MtaStatus::Cancelled,
+                Ok(Err(e)) => {
+                    set_last_error(e.to_string());
+                    MtaStatus::Error
+                }
+                Err(panic) => {
+                    set_last_error(panic_message(panic.as_ref()));
+                    MtaStatus::Error
+                }
+            };
+        }
+        self.status
+    }
+}
+
+/// Starts converting the video at `input_path` to an ascii video at `output_path` on a background thread.
+/// The job is freed with `mta_video_job_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_video_job_start(
+    input_path: *const c_char,
+    output_path: *const c_char,
+    options: *const MtaOptions,
+) -> *mut MtaVideoJob {
+    guard(|| {
+        let input_path = unsafe { str_arg(input_path, "input_path") }?;
+        let output_path = unsafe { str_arg(output_path, "output_path") }?;
+        let config = unsafe { video_config(options, input_path, output_path) }?;
+
+        let job = VideoJob::new();
+        let run_job = job.clone();
+        let handle = thread::Builder::new()
+            .name(format!("mediatoascii-job-{}", job.id()))
+            .spawn(move || run_job.run(config))
+            .map_err(|e| format!("Could not start video job: {e}"))?;
+        Ok(Box::into_raw(Box::new(MtaVideoJob { job, handle: Some(handle), status: MtaStatus::Ok })))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Overall progress of the job, from 0 to 1
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_video_job_progress(job: *const MtaVideoJob) -> f32 {
+    unsafe { job.as_ref() }.map_or(0.0, |job| job.job.progress().percentage())
+}
+
+/// Whether the job finished, so `mta_video_job_wait` returns without blocking
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_video_job_is_finished(job: *const MtaVideoJob) -> bool {
+    unsafe { job.as_ref() }.is_some_and(|job| job.handle.as_ref().is_none_or(JoinHandle::is_finished))
+}
+
+/// Requests cancellation.  The job stops at the next frame, and `mta_video_job_wait` returns
+/// `MTA_STATUS_CANCELLED`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_video_job_cancel(job: *const MtaVideoJob) {
+    if let Some(job) = unsafe { job.as_ref() } {
+        job.job.cancel();
+    }
+}
+
+/// Blocks until the job finishes, returning whether it succeeded
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_video_job_wait(job: *mut MtaVideoJob) -> MtaStatus {
+    match unsafe { job.as_mut() } {
+        Some(job) => {
+            let status = job.wait();
+            if status == MtaStatus::Ok {
+                clear_last_error();
+            }
+            status
+        }
+        None => {
+            set_last_error("job is NULL");
+            MtaStatus::Error
+        }
+    }
+}
+
+/// Frees the job, cancelling it and waiting for it to stop if it is still running
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mta_video_job_free(job: *mut MtaVideoJob) {
+    if !job.is_null() {
+        let mut job = unsafe { Box::from_raw(job) };
+        job.job.cancel();
+        job.wait();
+    }
+}
@@ -0,0 +1,128 @@
+//! WebAssembly bindings for converting images to ascii client-side, e.g. in a web page.
+//!
+//! Images are passed as RGBA bytes, such as the `data` of a canvas `ImageData`, and rendered ascii is returned
+//! as RGBA bytes that can be put back into an `ImageData`.
+
+use image::{DynamicImage, RgbaImage};
+use mediatoascii::ascii::{AsciiFrame, AsciiOptions, RenderOptions, ascii_from_image, render_ascii};
+use wasm_bindgen::Clamped;
+use wasm_bindgen::prelude::*;
+
+/// Settings for converting and rendering, see [AsciiOptions] and [RenderOptions]
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    /// Source pixels per ascii column
+    #[wasm_bindgen(js_name = scaleDown)]
+    pub scale_down: f32,
+    /// Source pixels per ascii row, relative to `scaleDown`
+    #[wasm_bindgen(js_name = heightSampleScale)]
+    pub height_sample_scale: f32,
+    /// Font size of rendered ascii
+    #[wasm_bindgen(js_name = fontSize)]
+    pub font_size: f32,
+    /// Invert the greyscale ramp, and render dark text on a light background
+    pub invert: bool,
+}
+
+#[wasm_bindgen]
+impl Options {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        let ascii = AsciiOptions::default();
+        let render = RenderOptions::default();
+        Options {
+            scale_down: ascii.scale_down,
+            height_sample_scale: ascii.height_sample_scale,
+            font_size: render.font_size,
+            invert: ascii.invert,
+        }
+    }
+}
+
+impl From<&Options> for AsciiOptions {
+    fn from(options: &Options) -> Self {
+        AsciiOptions {
+            scale_down: options.scale_down,
+            height_sample_scale: options.height_sample_scale,
+            invert: options.invert,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&Options> for RenderOptions {
+    fn from(options: &Options) -> Self {
+        RenderOptions {
+            font_size: options.font_size,
+            height_sample_scale: options.height_sample_scale,
+            invert: options.invert,
+        }
+    }
+}
+
+/// Rendered ascii image
+#[wasm_bindgen]
+pub struct RenderedImage {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl RenderedImage {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// RGBA bytes of the image, row by row, as a `Uint8ClampedArray` for `new ImageData(rgba, width)`.  Moves
+    /// the bytes out rather than copying them, so the image can't be used afterwards, and its width should be
+    /// read first.
+    #[wasm_bindgen(js_name = intoRgba)]
+    pub fn into_rgba(self) -> Clamped<Vec<u8>> {
+        Clamped(self.rgba)
+    }
+}
+
+/// Converts `width` x `height` RGBA pixels to ascii text, with each row ending in a newline
+#[wasm_bindgen(js_name = imageToAscii)]
+pub fn image_to_ascii(rgba: &[u8], width: u32, height: u32, options: &Options) -> Result<String, JsError> {
+    Ok(convert(rgba, width, height, options)?.to_string())
+}
+
+/// Renders ascii text, such as returned by `imageToAscii`, to an image
+#[wasm_bindgen(js_name = renderAscii)]
+pub fn render_ascii_text(text: &str, options: &Options) -> RenderedImage {
+    render(&AsciiFrame::from_text(text), options)
+}
+
+/// Converts `width` x `height` RGBA pixels to ascii, and renders it to an image
+#[wasm_bindgen(js_name = imageToAsciiImage)]
+pub fn image_to_ascii_image(rgba: &[u8], width: u32, height: u32, options: &Options) -> Result<RenderedImage, JsError> {
+    Ok(render(&convert(rgba, width, height, options)?, options))
+}
+
+fn convert(rgba: &[u8], width: u32, height: u32, options: &Options) -> Result<AsciiFrame, JsError> {
+    let img = RgbaImage::from_raw(width, height, rgba.to_vec()).ok_or_else(|| {
+        let expected = width as usize * height as usize * 4;
+        JsError::new(&format!("Expected {expected} RGBA bytes for a {width}x{height} image, got {}", rgba.len()))
+    })?;
+    Ok(ascii_from_image(&DynamicImage::ImageRgba8(img), &options.into()))
+}
+
+fn render(ascii: &AsciiFrame, options: &Options) -> RenderedImage {
+    let img = DynamicImage::ImageRgb8(render_ascii(ascii, &options.into())).into_rgba8();
+    RenderedImage { width: img.width(), height: img.height(), rgba: img.into_raw() }
+}
@@ -0,0 +1,36 @@
+#![cfg(target_arch = "wasm32")]
+
+use mediatoascii_wasm::{Options, image_to_ascii, image_to_ascii_image, render_ascii_text};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn options() -> Options {
+    Options { scale_down: 1.0, height_sample_scale: 2.0, ..Options::new() }
+}
+
+#[wasm_bindgen_test]
+fn converts_rgba_to_ascii() {
+    // 2 rows of white pixels above 2 rows of black pixels, 4 pixels wide
+    let rgba: Vec<u8> = [[255; 4]; 8].into_iter().chain([[0, 0, 0, 255]; 8]).flatten().collect();
+
+    assert_eq!(image_to_ascii(&rgba, 4, 4, &options()).unwrap(), "@@@@\n    \n");
+}
+
+#[wasm_bindgen_test]
+fn rejects_short_buffers() {
+    assert!(image_to_ascii(&[0; 12], 2, 2, &options()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn renders_ascii_to_rgba() {
+    let rendered = render_ascii_text("@@@@\n    \n", &options());
+
+    assert_eq!((rendered.width(), rendered.height()), (24, 24));
+    assert_eq!(rendered.into_rgba().0.len(), 24 * 24 * 4);
+}
+
+#[wasm_bindgen_test]
+fn renders_rgba_to_ascii_image() {
+    let rendered = image_to_ascii_image(&[255; 4 * 4 * 4], 4, 4, &options()).unwrap();
+
+    assert_eq!((rendered.width(), rendered.height()), (24, 24));
+}
@@ -0,0 +1,165 @@
+use derive_builder::Builder;
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+use rayon::prelude::{IndexedParallelIterator, ParallelIterator, ParallelSliceMut};
+use serde::Deserialize;
+
+use crate::ascii::AsciiFrame;
+use crate::image::generate_ascii_image;
+use crate::util::constants::{GREYSCALE_RAMP, MAGIC_HEIGHT_TO_WIDTH_RATIO, REVERSE_GREYSCALE_RAMP, RGB_TO_GREYSCALE};
+use crate::util::get_size_from_dimensions;
+
+/// Settings for converting pixels to ascii
+#[derive(Builder, Clone, Debug, Deserialize)]
+#[builder(default)]
+#[serde(default)]
+pub struct AsciiOptions {
+    /// Source pixels per ascii column
+    pub scale_down: f32,
+    /// Source pixels per ascii row, relative to `scale_down`, as characters are taller than they are wide
+    pub height_sample_scale: f32,
+    /// Invert the greyscale ramp, for light backgrounds
+    pub invert: bool,
+    /// Keep the source color of each cell as its foreground color
+    pub colors: bool,
+}
+
+impl Default for AsciiOptions {
+    fn default() -> Self {
+        AsciiOptions { scale_down: 1.0, height_sample_scale: MAGIC_HEIGHT_TO_WIDTH_RATIO, invert: false, colors: false }
+    }
+}
+
+/// Settings for rendering ascii to an image
+#[derive(Builder, Clone, Debug, Deserialize)]
+#[builder(default)]
+#[serde(default)]
+pub struct RenderOptions {
+    pub font_size: f32,
+    /// Same as the [AsciiOptions::height_sample_scale] the ascii was converted with, so the rendered image
+    /// keeps the source's aspect ratio
+    pub height_sample_scale: f32,
+    /// Draw dark text on a light background
+    pub invert: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions { font_size: 12.0, height_sample_scale: MAGIC_HEIGHT_TO_WIDTH_RATIO, invert: false }
+    }
+}
+
+/// Converts an image to ascii in memory
+pub fn ascii_from_image(img: &DynamicImage, options: &AsciiOptions) -> AsciiFrame {
+    let (width, height) = img.dimensions();
+    ascii_from_pixels(width, height, options, |x, y| img.get_pixel(x, y).0)
+}
+
+/// Converts `width` x `height` pixels to ascii, sampling the RGBA color of each cell with `pixel`.  Fully
+/// transparent pixels are left blank, but still keep their color and luminance.
+pub(crate) fn ascii_from_pixels<F>(width: u32, height: u32, options: &AsciiOptions, pixel: F) -> AsciiFrame
+where
+    F: Fn(u32, u32) -> [u8; 4] + Sync,
+{
+    let scale_down = options.scale_down;
+    let height_sample_scale = options.height_sample_scale;
+    let greyscale_ramp: &[&str] = if options.invert { REVERSE_GREYSCALE_RAMP } else { GREYSCALE_RAMP };
+
+    let columns = (width as f32 / scale_down) as usize;
+    let rows = ((height as f32 / scale_down) / height_sample_scale) as usize;
+    let mut frame = AsciiFrame::new(columns, rows);
+    if frame.is_empty() {
+        return frame;
+    }
+
+    frame.cells.par_chunks_mut(columns).enumerate().for_each(|(y, row)| {
+        let src_y = (y as f32 * scale_down * height_sample_scale) as u32;
+        for (x, cell) in row.iter_mut().enumerate() {
+            let [r, g, b, a] = pixel((x as f32 * scale_down) as u32, src_y);
+            let greyscale_value =
+                RGB_TO_GREYSCALE.0 * r as f32 + RGB_TO_GREYSCALE.1 * g as f32 + RGB_TO_GREYSCALE.2 * b as f32;
+            cell.fg = options.colors.then_some(Rgb([r, g, b]));
+            cell.luminance = Some(greyscale_value as u8);
+            if a != 0 {
+                let index = (greyscale_value * (greyscale_ramp.len() - 1) as f32 / 255.0).ceil() as usize;
+                cell.ch = greyscale_ramp[index].chars().next().unwrap_or(' ');
+            }
+        }
+    });
+
+    frame
+}
+
+/// Renders ascii to an image in memory, sized to fit the ascii at `options.font_size`
+pub fn render_ascii(frame: &AsciiFrame, options: &RenderOptions) -> RgbImage {
+    let (width, height) =
+        get_size_from_dimensions(frame.width, frame.height, options.height_sample_scale, options.font_size);
+    generate_ascii_image(frame, width, height, options.invert, options.font_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::*;
+
+    fn options(scale_down: f32, height_sample_scale: f32) -> AsciiOptions {
+        AsciiOptions { scale_down, height_sample_scale, ..AsciiOptions::default() }
+    }
+
+    #[test]
+    fn scales_columns_and_rows() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(40, 41));
+        let frame = ascii_from_image(&img, &options(2.0, MAGIC_HEIGHT_TO_WIDTH_RATIO));
+        // Rows are also scaled by the height to width ratio of characters, rounding down
+        assert_eq!((frame.width, frame.height), (20, 10));
+        assert_eq!(frame.cells.len(), 200);
+    }
+
+    #[test]
+    fn samples_the_top_left_pixel_of_each_cell() {
+        // White left half, black right half
+        let frame = ascii_from_pixels(4, 4, &options(2.0, 1.0), |x, _| if x < 2 { [255; 4] } else { [0, 0, 0, 255] });
+        assert_eq!(frame.to_string(), "@ \n@ \n");
+
+        let inverted = AsciiOptions { invert: true, ..options(2.0, 1.0) };
+        let frame = ascii_from_pixels(4, 4, &inverted, |x, _| if x < 2 { [255; 4] } else { [0, 0, 0, 255] });
+        assert_eq!(frame.to_string(), " @\n @\n");
+    }
+
+    #[test]
+    fn keeps_colors_only_if_enabled() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([200, 100, 50, 255])));
+
+        let frame = ascii_from_image(&img, &options(1.0, 1.0));
+        assert!(frame.cells.iter().all(|cell| cell.fg.is_none() && cell.luminance == Some(124)));
+        assert_eq!(frame.colors(), None);
+
+        let frame = ascii_from_image(&img, &AsciiOptions { colors: true, ..options(1.0, 1.0) });
+        assert!(frame.cells.iter().all(|cell| cell.fg == Some(Rgb([200, 100, 50]))));
+        assert_eq!(frame.colors(), Some(vec![vec![[200, 100, 50]; 2]; 2]));
+    }
+
+    #[test]
+    fn transparent_pixels_are_blank() {
+        let frame = ascii_from_pixels(1, 1, &options(1.0, 1.0), |_, _| [255, 255, 255, 0]);
+        assert_eq!(frame.get(0, 0).map(|cell| (cell.ch, cell.luminance)), Some((' ', Some(255))));
+    }
+
+    #[test]
+    fn images_narrower_than_a_cell_have_no_columns() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(1, 10));
+        let frame = ascii_from_image(&img, &options(2.0, 1.0));
+        assert!(frame.is_empty());
+        assert_eq!((frame.width, frame.height), (0, 5));
+    }
+
+    #[test]
+    fn renders_at_font_size() {
+        let frame = AsciiFrame::from_text("@@@\n...");
+        let image = render_ascii(&frame, &RenderOptions { font_size: 12.0, height_sample_scale: 2.0, invert: false });
+        assert_eq!(image.dimensions(), (18, 24));
+
+        let image = render_ascii(&AsciiFrame::new(0, 5), &RenderOptions::default());
+        assert_eq!(image.width(), 0);
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+
+use image::Rgb;
+
+/// A single character of ascii art
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    /// Color to draw the character with, such as the source color it was sampled from
+    pub fg: Option<Rgb<u8>>,
+    /// Color to draw behind the character
+    pub bg: Option<Rgb<u8>>,
+    /// Source greyscale luminance (0-255), which the character was picked from
+    pub luminance: Option<u8>,
+}
+
+impl Cell {
+    pub fn new(ch: char) -> Self {
+        Self { ch, fg: None, bg: None, luminance: None }
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self::new(' ')
+    }
+}
+
+impl From<char> for Cell {
+    fn from(ch: char) -> Self {
+        Self::new(ch)
+    }
+}
+
+/// Owned grid of ascii cells, stored row by row.
+///
+/// Displays as its characters, with each row ending in a newline.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AsciiFrame {
+    /// Number of columns
+    pub width: usize,
+    /// Number of rows
+    pub height: usize,
+    /// `width * height` cells, row by row
+    pub cells: Vec<Cell>,
+}
+
+impl AsciiFrame {
+    /// Blank frame of `width` x `height` spaces
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, cells: vec![Cell::default(); width * height] }
+    }
+
+    /// Frame of the characters of each line of `text`, padding shorter lines with spaces so every row is as
+    /// wide as the longest line
+    pub fn from_text(text: &str) -> Self {
+        let width = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let mut frame = Self::new(width, text.lines().count());
+        for (row, line) in frame.rows_mut().zip(text.lines()) {
+            for (cell, ch) in row.iter_mut().zip(line.chars()) {
+                cell.ch = ch;
+            }
+        }
+        frame
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
+        if x < self.width { self.cells.get(y * self.width + x) } else { None }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut Cell> {
+        if x < self.width { self.cells.get_mut(y * self.width + x) } else { None }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Cells of each row, from the top.  Frames without columns still have `height` rows, which are empty.
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[Cell]> + DoubleEndedIterator {
+        // Sliced rather than chunked, as chunks of 0 aren't allowed
+        (0..self.height).map(|y| self.cells.get(y * self.width..(y + 1) * self.width).unwrap_or_default())
+    }
+
+    /// Cells of each row, from the top.  Frames without columns have no cells to change, so no rows.
+    pub fn rows_mut(&mut self) -> impl ExactSizeIterator<Item = &mut [Cell]> + DoubleEndedIterator {
+        self.cells.chunks_mut(self.width.max(1))
+    }
+
+    /// Characters of each row, as a string per row
+    pub fn row_strings(&self) -> Vec<String> {
+        self.rows().map(|row| row.iter().map(|cell| cell.ch).collect()).collect()
+    }
+
+    /// Characters of the frame, with each row ending in `line_ending`
+    pub fn to_text(&self, line_ending: &str) -> String {
+        let mut buffer = String::with_capacity(self.cells.len() + self.height * line_ending.len());
+        for row in self.rows() {
+            buffer.extend(row.iter().map(|cell| cell.ch));
+            buffer.push_str(line_ending);
+        }
+        buffer
+    }
+
+    /// Source color of each cell, or `None` if any cell has no color, as the frame wasn't converted with colors
+    pub fn colors(&self) -> Option<Vec<Vec<[u8; 3]>>> {
+        self.rows().map(|row| row.iter().map(|cell| cell.fg.map(|color| color.0)).collect()).collect()
+    }
+
+    /// Source luminance of each cell, or `None` if any cell has no luminance, such as frames read from text
+    pub fn luminance(&self) -> Option<Vec<Vec<u8>>> {
+        self.rows().map(|row| row.iter().map(|cell| cell.luminance).collect()).collect()
+    }
+}
+
+impl fmt::Display for AsciiFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_text("\n"))
+    }
+}
+
+impl From<&str> for AsciiFrame {
+    fn from(text: &str) -> Self {
+        Self::from_text(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_text_reads_rows() {
+        let frame = AsciiFrame::from_text("#+-\n-+#\n");
+        assert_eq!((frame.width, frame.height), (3, 2));
+        assert_eq!(frame.row_strings(), vec!["#+-", "-+#"]);
+        assert_eq!(frame.get(2, 1), Some(&Cell::new('#')));
+    }
+
+    #[test]
+    fn from_text_pads_short_lines() {
+        let frame = AsciiFrame::from_text("#\n###\n##");
+        assert_eq!((frame.width, frame.height), (3, 3));
+        assert_eq!(frame.to_string(), "#  \n###\n## \n");
+    }
+
+    #[test]
+    fn from_text_counts_characters_not_bytes() {
+        let frame = AsciiFrame::from_text("é░█\n");
+        assert_eq!((frame.width, frame.height), (3, 1));
+        assert_eq!(frame.to_string(), "é░█\n");
+    }
+
+    #[test]
+    fn from_text_strips_line_endings() {
+        assert_eq!(AsciiFrame::from_text("ab\r\ncd\r\n"), AsciiFrame::from_text("ab\ncd"));
+    }
+
+    #[test]
+    fn from_text_round_trips_display() {
+        let text = "@%#*\n+=-:\n.   \n";
+        assert_eq!(AsciiFrame::from_text(text).to_string(), text);
+        assert_eq!(AsciiFrame::from(text), AsciiFrame::from_text(text));
+    }
+
+    #[test]
+    fn from_text_empty() {
+        let frame = AsciiFrame::from_text("");
+        assert!(frame.is_empty());
+        assert_eq!((frame.width, frame.height), (0, 0));
+        assert_eq!(frame.to_string(), "");
+    }
+
+    #[test]
+    fn from_text_blank_lines() {
+        let frame = AsciiFrame::from_text("\n\n");
+        assert!(frame.is_empty());
+        assert_eq!((frame.width, frame.height), (0, 2));
+        assert_eq!(frame.to_string(), "\n\n");
+        assert_eq!(frame.row_strings(), ["", ""]);
+        assert_eq!(frame.colors(), Some(vec![vec![], vec![]]));
+    }
+
+    #[test]
+    fn get_out_of_bounds() {
+        let frame = AsciiFrame::from_text("ab\ncd");
+        assert_eq!(frame.get(2, 0), None);
+        assert_eq!(frame.get(0, 2), None);
+    }
+}
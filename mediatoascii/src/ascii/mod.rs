@@ -0,0 +1,5 @@
+mod convert;
+mod frame;
+
+pub use convert::*;
+pub use frame::*;
@@ -0,0 +1,20 @@
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to read image: {0}")]
+    ImageReadError(String),
+    #[error("Failed to write output: {0}")]
+    ImageWriteError(String),
+}
+
+/// Manually implement Serialize to work with tauri
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
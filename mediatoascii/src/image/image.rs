@@ -1,17 +1,30 @@
 use ab_glyph::PxScale;
+#[cfg(feature = "terminal")]
+use crossterm::terminal;
 use derive_builder::Builder;
-use image::{GenericImageView, ImageBuffer, Rgb, RgbImage};
+use image::{DynamicImage, ImageBuffer, ImageError, ImageFormat, Rgb, RgbImage};
 use imageproc::drawing::draw_text_mut;
-use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon::prelude::{IndexedParallelIterator, ParallelIterator, ParallelSlice};
 use std::cell::UnsafeCell;
+use std::io::Cursor;
+use tracing::{debug, error, info, info_span};
 
-use crate::util::constants::{
-    BLACK_RGB, CASCADIA_FONT, DARK_RGB, GREYSCALE_RAMP, MAGIC_HEIGHT_TO_WIDTH_RATIO, REVERSE_GREYSCALE_RAMP,
-    RGB_TO_GREYSCALE, WHITE_RGB,
-};
+use crate::ascii::{AsciiFrame, AsciiOptions, RenderOptions, ascii_from_image};
+use crate::image::errors::Error;
+use crate::util::ansi::ascii_to_ansi_str;
+use crate::util::constants::{BLACK_RGB, CASCADIA_FONT, DARK_RGB, MAGIC_HEIGHT_TO_WIDTH_RATIO, WHITE_RGB};
+use crate::util::export::{AsciiExport, AsciiExportFrame, ExportFormat};
 use crate::util::file_util::{check_file_exists, check_valid_file, write_to_file};
+#[cfg(feature = "terminal")]
+use crate::util::fit_scale_down;
+use crate::util::progress::{ProgressEvent, ProgressObserver, Stage};
+use crate::util::stdio::{PeekedStdin, is_stdio, write_stdout};
+#[cfg(feature = "async")]
+use crate::util::task::Task;
 use crate::util::{UnsafeImageBuffer, get_size_from_ascii, print_ascii};
 
+pub type ImageResult<T> = Result<T, crate::image::errors::Error>;
+
 #[derive(Builder, Debug)]
 #[builder(default)]
 pub struct ImageConfig {
@@ -22,6 +35,23 @@ pub struct ImageConfig {
     invert: bool,
     output_file_path: Option<String>,
     output_image_path: Option<String>,
+    /// Output path for ANSI art (.ans) with 24-bit color codes sampled from the input image
+    output_ansi_path: Option<String>,
+    /// Output path for a structured (.json, or .msgpack with the `msgpack` feature) export of the ascii
+    output_export_path: Option<String>,
+    /// Format of the structured export.  Defaults to the format of the export path's extension, or JSON,
+    /// such as for exports written to stdout.
+    export_format: Option<ExportFormat>,
+    /// Include the source color of each cell in structured exports
+    export_colors: bool,
+    /// Include the source luminance of each cell in structured exports
+    export_luminance: bool,
+    /// Bytes already read from stdin, such as by [crate::util::media::detect_stdin_media_type], which are
+    /// read before the rest of stdin when `image_path` is `-`
+    stdin: PeekedStdin,
+    /// Fit ascii printed to the console to the terminal size, overriding `scale_down` while preserving the
+    /// aspect ratio.  Ignored without the `terminal` feature.
+    fit_terminal: bool,
     overwrite: bool,
 }
 
@@ -35,14 +65,42 @@ impl Default for ImageConfig {
             invert: false,
             output_file_path: None,
             output_image_path: None,
+            output_ansi_path: None,
+            output_export_path: None,
+            export_format: None,
+            export_colors: false,
+            export_luminance: false,
+            stdin: PeekedStdin::default(),
+            fit_terminal: false,
             overwrite: false,
         }
     }
 }
 
+impl From<&ImageConfig> for AsciiOptions {
+    fn from(config: &ImageConfig) -> Self {
+        AsciiOptions {
+            scale_down: config.scale_down,
+            height_sample_scale: config.height_sample_scale,
+            invert: config.invert,
+            colors: config.output_ansi_path.is_some() || config.export_colors,
+        }
+    }
+}
+
+impl From<&ImageConfig> for RenderOptions {
+    fn from(config: &ImageConfig) -> Self {
+        RenderOptions {
+            font_size: config.font_size,
+            height_sample_scale: config.height_sample_scale,
+            invert: config.invert,
+        }
+    }
+}
+
 #[inline]
 pub fn generate_ascii_image(
-    ascii: &[Vec<&str>],
+    ascii: &AsciiFrame,
     width: u32,
     height: u32,
     invert: bool,
@@ -59,8 +117,8 @@ pub fn generate_ascii_image(
     // });
 
     // SAFETY: Operates on pixels independently
-    ascii.par_iter().enumerate().for_each(|(row, row_data)| unsafe {
-        let text_row = row_data.join("");
+    ascii.cells.par_chunks(ascii.width.max(1)).enumerate().for_each(|(row, row_data)| unsafe {
+        let text_row: String = row_data.iter().map(|cell| cell.ch).collect();
         draw_text_mut(
             frame.get().as_mut().unwrap().as_mut().unwrap(),
             text_color,
@@ -78,71 +136,172 @@ pub fn generate_ascii_image(
 pub fn write_to_image<S: AsRef<str>>(
     output_file: S,
     overwrite: bool,
-    ascii: &[Vec<&str>],
+    ascii: &AsciiFrame,
     width: u32,
     height: u32,
     invert: bool,
     font_size: f32,
-) {
+) -> ImageResult<()> {
     let output_file = output_file.as_ref();
-    check_file_exists(output_file, overwrite);
-    match generate_ascii_image(ascii, width, height, invert, font_size).save(output_file) {
-        Ok(_) => {
-            println!("Successfully saved ascii image to {}", output_file);
-        }
-        Err(e) => {
-            eprintln!("Failed to save ascii image to {}: {}", output_file, e);
-        }
+    check_file_exists(output_file, overwrite).map_err(|e| Error::ImageWriteError(e.to_string()))?;
+    let image = generate_ascii_image(ascii, width, height, invert, font_size);
+    let result = if is_stdio(output_file) {
+        // Written as PNG, as there's no extension to pick the format from
+        let mut png = Cursor::new(Vec::new());
+        image
+            .write_to(&mut png, ImageFormat::Png)
+            .and_then(|_| write_stdout(png.get_ref()).map_err(ImageError::IoError))
+    } else {
+        image.save(output_file)
+    };
+    result.map_err(|e| Error::ImageWriteError(format!("Could not save ascii image to {output_file}: {e}")))?;
+    info!("Successfully saved ascii image to {}", output_file);
+    Ok(())
+}
+
+/// Writes ascii as ANSI art, colored with the source color of each cell, which `ascii` must be converted with
+pub fn write_to_ansi<S: AsRef<str>>(output_file: S, overwrite: bool, ascii: &AsciiFrame) -> ImageResult<()> {
+    let output_file = output_file.as_ref();
+    check_file_exists(output_file, overwrite).map_err(|e| Error::ImageWriteError(e.to_string()))?;
+    let ansi = ascii_to_ansi_str(ascii);
+    let result = if is_stdio(output_file) { write_stdout(ansi.as_bytes()) } else { std::fs::write(output_file, ansi) };
+    result.map_err(|e| Error::ImageWriteError(format!("Could not save ansi art to {output_file}: {e}")))?;
+    info!("Successfully saved ansi art to {}", output_file);
+    Ok(())
+}
+
+pub fn write_to_export<S: AsRef<str>>(
+    output_file: S,
+    overwrite: bool,
+    export: &AsciiExport,
+    format: ExportFormat,
+) -> ImageResult<()> {
+    let output_file = output_file.as_ref();
+    check_file_exists(output_file, overwrite).map_err(|e| Error::ImageWriteError(e.to_string()))?;
+    export
+        .write(output_file, format)
+        .map_err(|e| Error::ImageWriteError(format!("Could not save ascii export to {output_file}: {e}")))?;
+    info!("Successfully saved ascii export to {}", output_file);
+    Ok(())
+}
+
+fn open_image(config: &ImageConfig) -> ImageResult<DynamicImage> {
+    let img_path = config.image_path.as_str();
+    if is_stdio(img_path) {
+        let data = config
+            .stdin
+            .clone()
+            .read_to_end()
+            .map_err(|e| Error::ImageReadError(format!("Could not read image from stdin: {e}")))?;
+        return image::load_from_memory(&data)
+            .map_err(|e| Error::ImageReadError(format!("Image from stdin could not be decoded: {e}")));
     }
+    check_valid_file(img_path).map_err(|e| Error::ImageReadError(e.to_string()))?;
+    image::open(img_path).map_err(|e| Error::ImageReadError(format!("Image at {img_path} could not be opened: {e}")))
 }
 
 #[inline]
-pub fn convert_image_to_ascii(config: &ImageConfig) -> Vec<Vec<&'static str>> {
-    let img_path = config.image_path.as_str();
-    check_valid_file(img_path);
-    let scale_down = config.scale_down;
-    let height_sample_scale = config.height_sample_scale;
-
-    // Invert greyscale, for dark backgrounds
-    let greyscale_ramp: &[&str] = if config.invert { &REVERSE_GREYSCALE_RAMP } else { &GREYSCALE_RAMP };
-
-    let img = image::open(img_path).unwrap_or_else(|_| panic!("Image at {img_path} could not be opened"));
-    let (width, height) = img.dimensions();
-    let scaled_width = (width as f32 / scale_down) as usize;
-    let scaled_height = ((height as f32 / scale_down) / height_sample_scale) as usize;
-
-    let mut res = vec![vec![" "; scaled_width]; scaled_height];
-
-    for (y, row) in res.iter_mut().enumerate() {
-        for x in 0..scaled_width {
-            let pix =
-                img.get_pixel((x as f32 * scale_down) as u32, (y as f32 * scale_down * height_sample_scale) as u32);
-            if pix[3] != 0 {
-                let greyscale_value = RGB_TO_GREYSCALE.0 * pix[0] as f32
-                    + RGB_TO_GREYSCALE.1 * pix[1] as f32
-                    + RGB_TO_GREYSCALE.2 * pix[2] as f32;
-                let index = (greyscale_value * (greyscale_ramp.len() - 1) as f32 / 255.0).ceil() as usize;
-                row[x] = greyscale_ramp[index];
-            }
+pub fn convert_image_to_ascii(config: &ImageConfig) -> ImageResult<AsciiFrame> {
+    Ok(convert_dynamic_image_to_ascii(&open_image(config)?, config))
+}
+
+/// Converts an image to ascii, keeping the source color of each cell if it is written as ANSI art or
+/// exported with colors
+#[inline]
+pub fn convert_dynamic_image_to_ascii(img: &DynamicImage, config: &ImageConfig) -> AsciiFrame {
+    ascii_from_image(img, &config.into())
+}
+
+pub fn process_image(config: ImageConfig) -> ImageResult<()> {
+    process_image_with_progress(config, &())
+}
+
+/// Processes an image on tokio's blocking thread pool, resolving to the result when the returned [Task] is
+/// awaited.  Images can't be cancelled once started.  Must be called within a tokio runtime.
+#[cfg(feature = "async")]
+pub fn process_image_async(config: ImageConfig) -> Task<ImageResult<()>> {
+    Task::spawn(None, move |progress| process_image_with_progress(config, &progress))
+}
+
+/// Processes an image, reporting its progress to `observer` as a single frame
+pub fn process_image_with_progress(config: ImageConfig, observer: &dyn ProgressObserver) -> ImageResult<()> {
+    let _span = info_span!("image", input = %config.image_path).entered();
+
+    let result = run_image(config, observer);
+    match result.as_ref() {
+        Ok(()) => observer.on_progress(&ProgressEvent::Finished),
+        Err(e) => {
+            error!("Image conversion failed: {e}");
+            observer.on_progress(&ProgressEvent::Error { message: e.to_string() });
         }
     }
-
-    res
+    result
 }
 
-pub fn process_image(config: ImageConfig) {
-    let ascii = convert_image_to_ascii(&config);
+fn run_image(
+    #[cfg_attr(not(feature = "terminal"), allow(unused_mut))] mut config: ImageConfig,
+    observer: &dyn ProgressObserver,
+) -> ImageResult<()> {
+    debug!(?config, "Processing image");
+
+    let img = open_image(&config)?;
+    observer.on_progress(&ProgressEvent::ProbeFinished {
+        width: img.width(),
+        height: img.height(),
+        fps: None,
+        total_frames: Some(1),
+    });
+    observer.on_progress(&ProgressEvent::StageChanged { stage: Stage::Converting, total_frames: Some(1) });
+
+    let print_to_console = config.output_file_path.is_none()
+        && config.output_image_path.is_none()
+        && config.output_ansi_path.is_none()
+        && config.output_export_path.is_none();
+    #[cfg(feature = "terminal")]
+    if print_to_console
+        && config.fit_terminal
+        && let Ok((columns, rows)) = terminal::size()
+    {
+        // Leave a row for the prompt after the ascii
+        config.scale_down =
+            fit_scale_down(img.width(), img.height(), columns, rows.saturating_sub(1), config.height_sample_scale);
+    }
+
+    let ascii = convert_dynamic_image_to_ascii(&img, &config);
+    observer.on_progress(&ProgressEvent::FrameEncoded { index: 0 });
 
     if let Some(file) = config.output_file_path.as_ref() {
-        write_to_file(file, config.overwrite, &ascii);
+        write_to_file(file, config.overwrite, &ascii)
+            .map_err(|e| Error::ImageWriteError(format!("Could not save ascii text to {file}: {e}")))?;
     }
 
     if let Some(file) = config.output_image_path.as_ref() {
         let (width, height) = get_size_from_ascii(&ascii, config.height_sample_scale, config.font_size);
-        write_to_image(file, config.overwrite, &ascii, width, height, config.invert, config.font_size);
+        write_to_image(file, config.overwrite, &ascii, width, height, config.invert, config.font_size)?;
+    }
+
+    if let Some(file) = config.output_ansi_path.as_ref() {
+        write_to_ansi(file, config.overwrite, &ascii)?;
+    }
+
+    if let Some(file) = config.output_export_path.as_ref() {
+        let mut frame = AsciiExportFrame::new(0, 0.0, &ascii);
+        if config.export_colors {
+            frame = frame.with_colors(&ascii);
+        }
+        if config.export_luminance {
+            frame = frame.with_luminance(&ascii);
+        }
+
+        let mut export = AsciiExport::new(config.invert, None);
+        export.push_frame(frame);
+        let format = config.export_format.or_else(|| ExportFormat::from_path(file)).unwrap_or_default();
+        write_to_export(file, config.overwrite, &export, format)?;
     }
 
-    if config.output_file_path.is_none() && config.output_image_path.is_none() {
+    if print_to_console {
         print_ascii(&ascii);
     }
+    observer.on_progress(&ProgressEvent::FrameWritten { index: 0 });
+    Ok(())
 }
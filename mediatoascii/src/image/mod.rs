@@ -1,2 +1,3 @@
+mod errors;
 mod image;
 pub use self::image::*;
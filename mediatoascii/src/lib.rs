@@ -1,3 +1,5 @@
+pub mod ascii;
 pub mod image;
 pub mod util;
+#[cfg(feature = "ffmpeg")]
 pub mod video;
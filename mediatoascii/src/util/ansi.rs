@@ -0,0 +1,32 @@
+use crate::ascii::AsciiFrame;
+
+/// Control Sequence Introducer
+const CSI: &str = "\x1b[";
+/// Resets all ANSI graphics attributes
+pub const ANSI_RESET: &str = "\x1b[0m";
+
+/// Converts ascii to a string of ANSI art, coloring each cell's foreground with its 24-bit color.  Cells
+/// without a color are left in the terminal's default color.
+///
+/// Lines are terminated with `\r\n` so the output renders the same when `cat`ed to a terminal or
+/// loaded into an ANSI art viewer.
+pub fn ascii_to_ansi_str(ascii: &AsciiFrame) -> String {
+    let mut buffer = String::default();
+    for row in ascii.rows() {
+        let mut current_color = None;
+        for cell in row {
+            // Whitespace doesn't need a color, and skipping it keeps the output smaller
+            if let Some(color) = cell.fg
+                && cell.ch != ' '
+                && current_color != Some(color)
+            {
+                buffer.push_str(&format!("{CSI}38;2;{};{};{}m", color[0], color[1], color[2]));
+                current_color = Some(color);
+            }
+            buffer.push(cell.ch);
+        }
+        buffer.push_str(ANSI_RESET);
+        buffer.push_str("\r\n");
+    }
+    buffer
+}
@@ -1,5 +1,6 @@
 use ab_glyph::FontRef;
 use image::Rgb;
+#[cfg(feature = "opencv")]
 use opencv::core::Scalar;
 use std::sync::LazyLock;
 
@@ -12,8 +13,10 @@ pub static DARK_RGB: Rgb<u8> = Rgb([40u8, 42u8, 54u8]);
 /// Black RGB
 pub static BLACK_RGB: Rgb<u8> = Rgb([0u8, 0u8, 0u8]);
 /// White BGR scalar (opencv uses BGR)
+#[cfg(feature = "opencv")]
 pub static WHITE_BGR_SCALAR: Scalar = Scalar::new(255.0, 255.0, 255.0, 0.0);
 /// Faded black BGR scalar (opencv uses BGR)
+#[cfg(feature = "opencv")]
 pub static DARK_BGR_SCALAR: Scalar = Scalar::new(54.0, 42.0, 40.0, 0.0);
 
 /// When creating the output ascii video, for Cascadia font, this is a magic height to width ratio
@@ -0,0 +1,200 @@
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ascii::AsciiFrame;
+use crate::util::constants::{GREYSCALE_RAMP, REVERSE_GREYSCALE_RAMP};
+use crate::util::stdio::{STDIO_PATH, write_stdout};
+
+/// Serialization format of a structured export
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    /// Requires the `msgpack` feature
+    MessagePack,
+}
+
+impl ExportFormat {
+    /// Format of an export written to `path`, from its `.json`, `.msgpack` or `.mpk` extension
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?;
+        if extension.eq_ignore_ascii_case("json") {
+            Some(ExportFormat::Json)
+        } else if extension.eq_ignore_ascii_case("msgpack") || extension.eq_ignore_ascii_case("mpk") {
+            Some(ExportFormat::MessagePack)
+        } else {
+            None
+        }
+    }
+
+    /// Whether mediatoascii was built with support for writing this format
+    pub fn is_supported(self) -> bool {
+        match self {
+            ExportFormat::Json => true,
+            ExportFormat::MessagePack => cfg!(feature = "msgpack"),
+        }
+    }
+}
+
+/// Parses `json`, or `msgpack`/`mpk`, case-insensitively
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("json") {
+            Ok(ExportFormat::Json)
+        } else if s.eq_ignore_ascii_case("msgpack") || s.eq_ignore_ascii_case("mpk") {
+            Ok(ExportFormat::MessagePack)
+        } else {
+            Err(format!("Unknown export format {s}, expected json or msgpack"))
+        }
+    }
+}
+
+/// Structured ascii output of an image or video, for consumers that render the ascii themselves.
+///
+/// Images are exported as a single frame with a timestamp of 0.
+#[derive(Debug, Serialize)]
+pub struct AsciiExport {
+    /// Number of ascii columns
+    pub width: usize,
+    /// Number of ascii rows
+    pub height: usize,
+    /// Greyscale ramp used for the conversion, from darkest to brightest
+    pub ramp: &'static [&'static str],
+    /// Frames per second of the source video.  Omitted for images.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+    pub frames: Vec<AsciiExportFrame>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AsciiExportFrame {
+    pub index: u64,
+    /// Presentation timestamp in seconds
+    pub timestamp: f64,
+    /// Rows of ascii, with one character per cell
+    pub rows: Vec<String>,
+    /// Source RGB color of each cell
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colors: Option<Vec<Vec<[u8; 3]>>>,
+    /// Source greyscale luminance (0-255) of each cell, which the ramp character was picked from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub luminance: Option<Vec<Vec<u8>>>,
+}
+
+impl AsciiExport {
+    pub fn new(invert: bool, fps: Option<f64>) -> Self {
+        let ramp = if invert { REVERSE_GREYSCALE_RAMP } else { GREYSCALE_RAMP };
+        Self { width: 0, height: 0, ramp, fps, frames: vec![] }
+    }
+
+    /// Adds a frame, sizing the export to the first frame
+    pub fn push_frame(&mut self, frame: AsciiExportFrame) {
+        if self.frames.is_empty() {
+            self.width = frame.rows.first().map(|row| row.chars().count()).unwrap_or(0);
+            self.height = frame.rows.len();
+        }
+        self.frames.push(frame);
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        rmp_serde::to_vec_named(self)
+    }
+
+    /// Serializes the export in `format`
+    pub fn to_bytes(&self, format: ExportFormat) -> io::Result<Vec<u8>> {
+        match format {
+            ExportFormat::Json => Ok(self.to_json()?.into_bytes()),
+            ExportFormat::MessagePack => self.msgpack_bytes(),
+        }
+    }
+
+    /// Writes the export to `path` in `format`, or to stdout if the path is `-`
+    pub fn write<P: AsRef<Path>>(&self, path: P, format: ExportFormat) -> io::Result<()> {
+        let path = path.as_ref();
+        let bytes = self.to_bytes(format)?;
+        if path.as_os_str() == STDIO_PATH { write_stdout(&bytes) } else { std::fs::write(path, bytes) }
+    }
+
+    #[cfg(feature = "msgpack")]
+    fn msgpack_bytes(&self) -> io::Result<Vec<u8>> {
+        self.to_msgpack().map_err(io::Error::other)
+    }
+
+    #[cfg(not(feature = "msgpack"))]
+    fn msgpack_bytes(&self) -> io::Result<Vec<u8>> {
+        Err(io::Error::other("MessagePack output requires the `msgpack` feature"))
+    }
+}
+
+impl AsciiExportFrame {
+    pub fn new(index: u64, timestamp: f64, ascii: &AsciiFrame) -> Self {
+        Self { index, timestamp, rows: ascii.row_strings(), colors: None, luminance: None }
+    }
+
+    /// Includes the source color of each cell.  Colors are omitted if `ascii` wasn't converted with them.
+    pub fn with_colors(mut self, ascii: &AsciiFrame) -> Self {
+        self.colors = ascii.colors();
+        self
+    }
+
+    /// Includes the source luminance of each cell, or omits it if `ascii` has none, such as frames read from
+    /// text
+    pub fn with_luminance(mut self, ascii: &AsciiFrame) -> Self {
+        self.luminance = ascii.luminance();
+        self
+    }
+}
+
+/// Whether `path` is a structured export output, i.e. has a `.json`, `.msgpack` or `.mpk` extension
+pub fn is_export_path<P: AsRef<Path>>(path: P) -> bool {
+    ExportFormat::from_path(path).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ascii::{AsciiOptions, ascii_from_pixels};
+
+    #[test]
+    fn export_format_from_path_and_name() {
+        assert_eq!(ExportFormat::from_path("ascii.JSON"), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::from_path("ascii.msgpack"), Some(ExportFormat::MessagePack));
+        assert_eq!(ExportFormat::from_path("ascii.mpk"), Some(ExportFormat::MessagePack));
+        assert_eq!(ExportFormat::from_path("ascii.png"), None);
+        assert_eq!(ExportFormat::from_path("-"), None);
+
+        assert_eq!("json".parse(), Ok(ExportFormat::Json));
+        assert_eq!("MsgPack".parse(), Ok(ExportFormat::MessagePack));
+        assert!("yaml".parse::<ExportFormat>().is_err());
+        assert_eq!(ExportFormat::MessagePack.is_supported(), cfg!(feature = "msgpack"));
+    }
+
+    #[test]
+    fn includes_only_captured_colors_and_luminance() {
+        let convert = |colors| {
+            let options = AsciiOptions { scale_down: 1.0, height_sample_scale: 1.0, invert: false, colors };
+            ascii_from_pixels(2, 1, &options, |x, _| [x as u8 * 255, 0, 0, 255])
+        };
+
+        let frame = AsciiExportFrame::new(0, 0.0, &AsciiFrame::default());
+        let colored = frame.with_colors(&convert(true)).with_luminance(&convert(true));
+        assert_eq!(colored.colors, Some(vec![vec![[0, 0, 0], [255, 0, 0]]]));
+        assert!(colored.luminance.is_some());
+
+        let uncolored = colored.with_colors(&convert(false));
+        assert_eq!(uncolored.colors, None);
+        let text = AsciiFrame::from_text("#.\n");
+        let from_text = uncolored.with_colors(&text).with_luminance(&text);
+        assert_eq!((from_text.colors, from_text.luminance), (None, None));
+    }
+}
@@ -1,4 +1,8 @@
+use ffmpeg_next::format::Pixel;
 use ffmpeg_next::util::frame::video::Video as FfmpegVideoFrame;
+use image::RgbImage;
+
+use crate::ascii::{AsciiFrame, AsciiOptions, ascii_from_pixels};
 
 #[derive(Clone)]
 pub struct FFmpegFrame {
@@ -14,8 +18,21 @@ impl FFmpegFrame {
         Self { frame, width, height }
     }
 
+    /// Copies an RGB image into an RGB24 frame
+    pub fn from_rgb_image(image: &RgbImage) -> Self {
+        let mut frame = FfmpegVideoFrame::new(Pixel::RGB24, image.width(), image.height());
+        let stride = frame.stride(0);
+        let row_len = image.width() as usize * 3;
+        let data = frame.data_mut(0);
+        for (y, row) in image.as_raw().chunks_exact(row_len).enumerate() {
+            data[y * stride..y * stride + row_len].copy_from_slice(row);
+        }
+        Self::new(frame)
+    }
+
     pub fn get_pixel(&self, x: u32, y: u32) -> (u8, u8, u8) {
-        let offset = ((y * self.width + x) * 3) as usize;
+        // Rows may be padded for alignment, so offset by the stride rather than the width
+        let offset = y as usize * self.frame.stride(0) + x as usize * 3;
         let data = self.frame.data(0);
         (
             data[offset],     // R
@@ -27,6 +44,13 @@ impl FFmpegFrame {
     pub fn is_empty(&self) -> bool {
         self.width == 0 && self.height == 0
     }
+
+    pub fn to_ascii(&self, options: &AsciiOptions) -> AsciiFrame {
+        ascii_from_pixels(self.width, self.height, options, |x, y| {
+            let (r, g, b) = self.get_pixel(x, y);
+            [r, g, b, u8::MAX]
+        })
+    }
 }
 
 impl Default for FFmpegFrame {
@@ -1,37 +1,101 @@
 use std::fs::OpenOptions;
-use std::io::Write;
+use std::io::{self, Write};
 use std::path::Path;
 
-pub fn check_file_exists<S: AsRef<str>>(file: S, overwrite: bool) {
+use image::ImageFormat;
+
+use crate::ascii::AsciiFrame;
+use crate::util::stdio::{is_stdio, write_stdout};
+
+/// URL schemes of network streams that are read with ffmpeg instead of from disk
+const STREAM_URL_SCHEMES: [&str; 8] = ["http", "https", "rtsp", "rtsps", "rtmp", "rtmps", "srt", "udp"];
+
+/// Fails if an output file already exists and can't be overwritten
+pub fn check_file_exists<S: AsRef<str>>(file: S, overwrite: bool) -> io::Result<()> {
     let file = file.as_ref();
-    if !overwrite && Path::new(file).exists() {
-        panic!("File at {} already exists, and overwrite is set to false", file);
+    if !overwrite && !is_stdio(file) && Path::new(file).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("File at {} already exists, and overwrite is set to false", file),
+        ));
     }
+    Ok(())
 }
 
-pub fn check_valid_file<S: AsRef<str>>(path: S) {
+/// Fails if an input isn't a file, stdin or a stream URL
+pub fn check_valid_file<S: AsRef<str>>(path: S) -> io::Result<()> {
     let path = path.as_ref();
-    if !Path::new(path).is_file() {
-        panic!("Path at {} is not a valid file!", path)
+    if !is_stdio(path) && !is_stream_url(path) && !Path::new(path).is_file() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Path at {} is not a valid file!", path)));
     }
+    Ok(())
+}
+
+/// Whether `path` is the URL of a network stream, such as an http(s) video, an HLS playlist or an RTSP
+/// stream, rather than a path on disk.  This is for inputs: RTMP, SRT and UDP URLs given as the output video
+/// are pushed to as live outputs instead, see `video::is_live_output`.
+pub fn is_stream_url<S: AsRef<str>>(path: S) -> bool {
+    url_scheme(path).is_some_and(|scheme| STREAM_URL_SCHEMES.contains(&scheme.as_str()))
+}
+
+/// Lowercased scheme of `path` if it's a URL, e.g. `rtsp` for `RTSP://host/stream`.  Schemes are
+/// case-insensitive, so callers should always match on this rather than on the raw prefix of `path`.
+pub fn url_scheme<S: AsRef<str>>(path: S) -> Option<String> {
+    path.as_ref().split_once("://").map(|(scheme, _)| scheme.to_ascii_lowercase())
+}
+
+/// Whether `path` has the extension of an image format supported by the `image` crate
+pub fn is_image_file<P: AsRef<Path>>(path: P) -> bool {
+    ImageFormat::from_path(path).is_ok()
 }
 
-pub fn write_to_file<S: AsRef<str>>(output_file: S, overwrite: bool, ascii: &[Vec<&str>]) {
+pub fn write_to_file<S: AsRef<str>>(output_file: S, overwrite: bool, ascii: &AsciiFrame) -> io::Result<()> {
     let output_file = output_file.as_ref();
-    check_file_exists(output_file, overwrite);
+    check_file_exists(output_file, overwrite)?;
+
+    let text = ascii.to_text("\r\n");
+    if is_stdio(output_file) {
+        return write_stdout(text.as_bytes());
+    }
 
     // TODO: change to create_new
-    let file_option = OpenOptions::new().write(true).create(true).truncate(true).open(output_file);
-
-    match file_option {
-        Ok(mut file) => {
-            for row in ascii {
-                file.write_all(row.join("").as_bytes()).unwrap();
-                file.write_all("\r\n".as_bytes()).unwrap();
-            }
+    OpenOptions::new().write(true).create(true).truncate(true).open(output_file)?.write_all(text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_stream_urls() {
+        for url in [
+            "http://example.com/video.mp4",
+            "https://example.com/live/index.m3u8",
+            "rtsp://192.168.1.10:554/stream",
+            "RTMP://example.com/live/key",
+            "rtmps://example.com/live/key",
+            "srt://example.com:9000",
+            "udp://239.0.0.1:1234",
+        ] {
+            assert!(is_stream_url(url), "{url}");
         }
-        Err(_) => {
-            panic!("Could not write output to file {}", output_file);
+    }
+
+    #[test]
+    fn rejects_local_paths() {
+        for path in ["video.mp4", "/videos/video.mp4", "C:\\videos\\video.mp4", "-", "", "://video.mp4"] {
+            assert!(!is_stream_url(path), "{path}");
         }
+        // Only network schemes are streams
+        assert!(!is_stream_url("file:///videos/video.mp4"));
+        assert!(!is_stream_url("ftp://example.com/video.mp4"));
+        assert!(!is_stream_url("http:/example.com/video.mp4"));
+    }
+
+    #[test]
+    fn parses_lowercased_url_schemes() {
+        assert_eq!(url_scheme("RTSP://192.168.1.10:554/stream").as_deref(), Some("rtsp"));
+        assert_eq!(url_scheme("Https://example.com/live/index.m3u8").as_deref(), Some("https"));
+        assert_eq!(url_scheme("video.mp4"), None);
     }
 }
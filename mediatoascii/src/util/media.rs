@@ -0,0 +1,94 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+#[cfg(feature = "ffmpeg")]
+use ffmpeg_next::format::input;
+#[cfg(feature = "ffmpeg")]
+use ffmpeg_next::media::Type;
+
+#[cfg(feature = "ffmpeg")]
+use crate::util::file_util::is_stream_url;
+use crate::util::stdio::{PeekedStdin, is_stdio};
+#[cfg(feature = "ffmpeg")]
+use crate::video::{is_image_sequence, is_text_video};
+
+/// Number of leading bytes read to guess an image format, enough for the magic of every format the
+/// `image` crate supports
+const MAGIC_BYTES_LEN: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaType {
+    Image,
+    /// Video files, and the other video inputs like image sequences and text videos
+    Video,
+}
+
+/// Probes the media at `path`, first matching image formats by their magic bytes, then probing the
+/// file with ffmpeg for a video stream.  Returns `None` if it is neither a supported image nor
+/// video.
+///
+/// Directories and printf-style patterns of images are image sequences, and text video directories
+/// and network stream URLs are only read by mediatoascii or ffmpeg, so are all detected as videos.
+/// Piped input from stdin, with a path of `-`, is detected with [detect_stdin_media_type] instead, as
+/// detecting it consumes some of it.  Without the `ffmpeg` feature, only images are detected.
+pub fn detect_media_type(path: &str) -> Option<MediaType> {
+    if is_stdio(path) {
+        return None;
+    }
+    if is_video_source(path) {
+        return Some(MediaType::Video);
+    }
+    if !Path::new(path).is_file() {
+        return None;
+    }
+
+    let mut magic = Vec::with_capacity(MAGIC_BYTES_LEN);
+    let file = File::open(path).ok()?;
+    file.take(MAGIC_BYTES_LEN as u64).read_to_end(&mut magic).ok()?;
+    if image::guess_format(&magic).is_ok() {
+        return Some(MediaType::Image);
+    }
+
+    probe_video(path)
+}
+
+/// Detects the media type of input piped to stdin from its magic bytes.  stdin can only be peeked at, so
+/// anything that isn't an image is assumed to be a video, and `None` is only returned if stdin is empty, or
+/// without the `ffmpeg` feature.
+///
+/// The bytes read are returned, and must be passed on to the config of the conversion so they are read
+/// again before the rest of stdin.
+pub fn detect_stdin_media_type() -> io::Result<(Option<MediaType>, PeekedStdin)> {
+    let stdin = PeekedStdin::peek(MAGIC_BYTES_LEN)?;
+    let media_type = if stdin.bytes().is_empty() {
+        None
+    } else if image::guess_format(stdin.bytes()).is_ok() {
+        Some(MediaType::Image)
+    } else {
+        cfg!(feature = "ffmpeg").then_some(MediaType::Video)
+    };
+    Ok((media_type, stdin))
+}
+
+/// Video inputs that aren't video files, so aren't probed
+#[cfg(feature = "ffmpeg")]
+fn is_video_source(path: &str) -> bool {
+    is_stream_url(path) || is_text_video(path) || is_image_sequence(path)
+}
+
+#[cfg(not(feature = "ffmpeg"))]
+fn is_video_source(_path: &str) -> bool {
+    false
+}
+
+#[cfg(feature = "ffmpeg")]
+fn probe_video(path: &str) -> Option<MediaType> {
+    ffmpeg_next::init().ok()?;
+    input(path).ok()?.streams().best(Type::Video).map(|_| MediaType::Video)
+}
+
+#[cfg(not(feature = "ffmpeg"))]
+fn probe_video(_path: &str) -> Option<MediaType> {
+    None
+}
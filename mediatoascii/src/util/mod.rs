@@ -2,19 +2,34 @@ use std::cell::UnsafeCell;
 use std::ops::{Deref, DerefMut};
 
 use image::{ImageBuffer, Rgb};
+#[cfg(feature = "opencv")]
 use opencv::core::Mat;
 
+use crate::ascii::AsciiFrame;
+
+pub mod ansi;
 pub mod constants;
+pub mod export;
+#[cfg(feature = "ffmpeg")]
 pub mod ffmpeg;
 pub mod file_util;
+pub mod media;
+pub mod progress;
+pub mod stdio;
+#[cfg(feature = "async")]
+pub mod task;
 
+#[cfg(feature = "ffmpeg")]
 pub use ffmpeg::FFmpegFrame;
 
 /// Wrapper around Mat that let's us bypass non-Sync since Mat uses *mut c_void ptr.  Tricks
 /// compiler into letting us use this across threads even though it's unsafe.  Allows for
 /// parallelization of some operations at very high performance.
+#[cfg(feature = "opencv")]
 pub struct UnsafeMat(pub Mat);
+#[cfg(feature = "opencv")]
 unsafe impl Sync for UnsafeMat {}
+#[cfg(feature = "opencv")]
 impl Deref for UnsafeMat {
     type Target = Mat;
 
@@ -23,6 +38,7 @@ impl Deref for UnsafeMat {
     }
 }
 
+#[cfg(feature = "opencv")]
 impl DerefMut for UnsafeMat {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
@@ -48,22 +64,23 @@ impl DerefMut for UnsafeImageBuffer {
     }
 }
 
-#[inline]
-pub fn ascii_to_str(ascii: &[Vec<&str>]) -> String {
-    let mut buffer = String::default();
-    for row in ascii {
-        for s in row {
-            buffer.push_str(s);
-        }
-        buffer.push('\n');
-    }
-    buffer
+pub fn print_ascii(ascii: &AsciiFrame) {
+    print!("{ascii}");
+}
+
+pub fn get_size_from_ascii(ascii: &AsciiFrame, height_sample_scale: f32, font_size: f32) -> (u32, u32) {
+    get_size_from_dimensions(ascii.width, ascii.height, height_sample_scale, font_size)
 }
 
-pub fn print_ascii(ascii: &[Vec<&str>]) {
-    print!("{}", ascii_to_str(ascii));
+/// Output image size for `columns` x `rows` of ascii characters
+pub fn get_size_from_dimensions(columns: usize, rows: usize, height_sample_scale: f32, font_size: f32) -> (u32, u32) {
+    ((columns as f32 * font_size / height_sample_scale) as u32, (rows as f32 * font_size) as u32)
 }
 
-pub fn get_size_from_ascii(ascii: &[Vec<&str>], height_sample_scale: f32, font_size: f32) -> (u32, u32) {
-    ((ascii[0].len() as f32 * font_size / height_sample_scale) as u32, (ascii.len() as f32 * font_size) as u32)
+/// Largest `scale_down` at which `width` x `height` frames convert to ascii that fits within `columns` x
+/// `rows` characters.  The same scale is used for both dimensions so the aspect ratio is preserved.
+pub fn fit_scale_down(width: u32, height: u32, columns: u16, rows: u16, height_sample_scale: f32) -> f32 {
+    let scale_to_width = width as f32 / columns.max(1) as f32;
+    let scale_to_height = height as f32 / (rows.max(1) as f32 * height_sample_scale);
+    scale_to_width.max(scale_to_height)
 }
@@ -0,0 +1,85 @@
+use std::sync::mpsc::{Sender, SyncSender};
+
+use serde::Serialize;
+
+/// Stage of processing media
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// Reading and decoding frames of a video written to a video file
+    Reading,
+    /// Converting read frames to ascii, and rendering them to output frames
+    Encoding,
+    /// Writing the rendered frames to the output video file
+    Writing,
+    /// Converting each frame to ascii and writing it as it is read, for outputs other than video files and
+    /// for images
+    Converting,
+    /// Playing the video in the terminal
+    Playing,
+}
+
+/// Progress of processing media, sent to a [ProgressObserver] as it happens
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// The input was opened.  `fps` is `None` for images, and `total_frames` is `None` for streams of unknown
+    /// length.
+    ProbeFinished { width: u32, height: u32, fps: Option<f64>, total_frames: Option<u64> },
+    /// A new stage started, which processes `total_frames` frames, or `None` if that isn't known yet
+    StageChanged { stage: Stage, total_frames: Option<u64> },
+    /// The frame at `index` of the stage was read
+    FrameRead { index: u64 },
+    /// The frame at `index` of the stage was converted to ascii and rendered
+    FrameEncoded { index: u64 },
+    /// The frame at `index` of the stage was written to the output
+    FrameWritten { index: u64 },
+    /// Estimated time left, from the progress so far.  `percentage` is from 0.0 to 1.0.
+    Eta { percentage: f32, remaining_seconds: f64 },
+    /// Processing finished successfully
+    Finished,
+    /// Processing failed, or was cancelled
+    Error { message: String },
+}
+
+/// Receives progress events while media is processed, so each consumer can render progress its own way.
+///
+/// Implemented for closures, and for channel senders to receive events on another thread.
+pub trait ProgressObserver: Send + Sync {
+    fn on_progress(&self, event: &ProgressEvent);
+}
+
+/// Ignores all progress
+impl ProgressObserver for () {
+    fn on_progress(&self, _event: &ProgressEvent) {}
+}
+
+impl<F: Fn(&ProgressEvent) + Send + Sync> ProgressObserver for F {
+    fn on_progress(&self, event: &ProgressEvent) {
+        self(event)
+    }
+}
+
+/// Sends events to a channel.  Events are dropped once the receiver is gone.
+impl ProgressObserver for Sender<ProgressEvent> {
+    fn on_progress(&self, event: &ProgressEvent) {
+        let _ = self.send(event.clone());
+    }
+}
+
+/// Sends events to a tokio channel, e.g. to receive them on an async runtime.  Events are dropped once the
+/// receiver is gone.
+#[cfg(feature = "async")]
+impl ProgressObserver for tokio::sync::mpsc::UnboundedSender<ProgressEvent> {
+    fn on_progress(&self, event: &ProgressEvent) {
+        let _ = self.send(event.clone());
+    }
+}
+
+/// Sends events to a bounded channel, blocking while it is full.  Events are dropped once the receiver is
+/// gone.
+impl ProgressObserver for SyncSender<ProgressEvent> {
+    fn on_progress(&self, event: &ProgressEvent) {
+        let _ = self.send(event.clone());
+    }
+}
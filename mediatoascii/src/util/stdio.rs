@@ -0,0 +1,49 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+/// Path of inputs read from stdin, and of outputs written to stdout
+pub const STDIO_PATH: &str = "-";
+
+/// Leading bytes of stdin that were already read, such as to detect the media type of piped input.
+///
+/// They are passed on with the config of the conversion reading stdin, and read again before the rest of
+/// stdin.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeekedStdin(Vec<u8>);
+
+impl PeekedStdin {
+    /// Reads the first `len` bytes of stdin, or fewer if it ends before then
+    pub fn peek(len: usize) -> io::Result<Self> {
+        let mut peeked = Vec::with_capacity(len);
+        io::stdin().lock().take(len as u64).read_to_end(&mut peeked)?;
+        Ok(Self(peeked))
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Reads stdin until it is closed, after the peeked bytes
+    pub fn read_to_end(self) -> io::Result<Vec<u8>> {
+        let mut data = self.0;
+        io::stdin().lock().read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// Whether `path` refers to stdin when used as an input, or stdout when used as an output
+pub fn is_stdio<S: AsRef<str>>(path: S) -> bool {
+    path.as_ref() == STDIO_PATH
+}
+
+/// Writes `data` to stdout, flushing it so it isn't interleaved with later output
+pub fn write_stdout(data: &[u8]) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(data)?;
+    stdout.flush()
+}
+
+/// Creates the output file at `path`, or returns stdout if the path is [STDIO_PATH]
+pub fn create_output(path: &str) -> io::Result<Box<dyn Write + Send>> {
+    if is_stdio(path) { Ok(Box::new(io::stdout())) } else { Ok(Box::new(File::create(path)?)) }
+}
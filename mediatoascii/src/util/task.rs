@@ -0,0 +1,111 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, ready};
+
+use futures_core::Stream;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio::task::JoinHandle;
+
+use crate::util::progress::{ProgressEvent, ProgressObserver};
+
+/// Progress events of a [Task], as a [Stream] that ends once the task finishes
+pub struct ProgressStream {
+    receiver: UnboundedReceiver<ProgressEvent>,
+}
+
+impl ProgressStream {
+    /// Waits for the next event, or `None` once the task finished.  Same as the [Stream] implementation, for
+    /// callers without a stream extension trait.
+    pub async fn next(&mut self) -> Option<ProgressEvent> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for ProgressStream {
+    type Item = ProgressEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Sends progress events of a [Task] to its [ProgressStream], once that's been taken.  Events before then are
+/// dropped, so they don't pile up for tasks whose progress is never taken.
+#[derive(Clone, Default)]
+pub(crate) struct ProgressSender(Arc<Mutex<Option<UnboundedSender<ProgressEvent>>>>);
+
+impl ProgressObserver for ProgressSender {
+    fn on_progress(&self, event: &ProgressEvent) {
+        if let Some(sender) = self.0.lock().unwrap().as_ref() {
+            let _ = sender.send(event.clone());
+        }
+    }
+}
+
+/// Media conversion running on tokio's blocking thread pool, so it doesn't tie up the async runtime.
+///
+/// Resolves to the result of the conversion when awaited, and streams its progress with
+/// [Task::take_progress].  Dropping the task before it finishes cancels it, if it can be cancelled, which
+/// video conversions are at the next frame.
+pub struct Task<T> {
+    handle: JoinHandle<T>,
+    /// Progress of the task, until the stream is taken
+    progress: Option<ProgressSender>,
+    /// Requests cancellation, for conversions that can be cancelled
+    cancellation: Option<Box<dyn Fn() + Send + Sync>>,
+    finished: bool,
+}
+
+impl<T: Send + 'static> Task<T> {
+    /// Runs `work` on the blocking thread pool, passing it the sender of the task's progress events.  Must be
+    /// called within a tokio runtime.
+    pub(crate) fn spawn<F>(cancellation: Option<Box<dyn Fn() + Send + Sync>>, work: F) -> Self
+    where
+        F: FnOnce(ProgressSender) -> T + Send + 'static,
+    {
+        let sender = ProgressSender::default();
+        let progress = sender.clone();
+        let handle = tokio::task::spawn_blocking(move || work(progress));
+        Self { handle, progress: Some(sender), cancellation, finished: false }
+    }
+}
+
+impl<T> Task<T> {
+    /// Takes the stream of progress events, from here on.  Returns `None` if it was already taken.
+    pub fn take_progress(&mut self) -> Option<ProgressStream> {
+        let sender = self.progress.take()?;
+        let (events, receiver) = unbounded_channel();
+        *sender.0.lock().unwrap() = Some(events);
+        Some(ProgressStream { receiver })
+    }
+
+    /// Requests cancellation, for tasks that can be cancelled.  Video conversions stop at the next frame.
+    pub fn cancel(&self) {
+        if let Some(cancellation) = self.cancellation.as_ref() {
+            cancellation();
+        }
+    }
+}
+
+impl<T> Future for Task<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = ready!(Pin::new(&mut self.handle).poll(cx));
+        self.finished = true;
+        // Panics in the conversion, such as for image errors, are raised where the task is awaited
+        Poll::Ready(result.unwrap_or_else(|e| match e.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(e) => panic!("Media conversion task failed: {e}"),
+        }))
+    }
+}
+
+impl<T> Drop for Task<T> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.cancel();
+        }
+    }
+}
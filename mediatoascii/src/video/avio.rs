@@ -0,0 +1,141 @@
+use std::ffi::{CString, c_int, c_void};
+use std::io::{Read, Seek, SeekFrom};
+use std::ptr;
+
+use ffmpeg_next::ffi;
+use ffmpeg_next::format::context::Input;
+
+use crate::video::VideoResult;
+use crate::video::errors::Error;
+
+/// Size of the buffer ffmpeg reads through, matching ffmpeg's default for file IO
+const AVIO_BUFFER_SIZE: usize = 32 * 1024;
+
+/// Media that ffmpeg can demux from memory or any other reader, rather than from a path
+pub(crate) trait MediaSource: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> MediaSource for T {}
+
+/// Custom ffmpeg IO context reading from a [MediaSource].
+///
+/// An [Input] opened with [open_input] doesn't own its IO context, so this must be dropped after it.
+pub(crate) struct AvioContext {
+    avio: *mut ffi::AVIOContext,
+    source: *mut Box<dyn MediaSource>,
+}
+
+// SAFETY: the IO context and source are only accessed through the input they are opened with, which is Send
+unsafe impl Send for AvioContext {}
+
+impl AvioContext {
+    fn new(source: Box<dyn MediaSource>) -> VideoResult<Self> {
+        unsafe {
+            let buffer = ffi::av_malloc(AVIO_BUFFER_SIZE) as *mut u8;
+            if buffer.is_null() {
+                return Err(Error::VideoReadError("ffmpeg could not allocate an IO buffer".to_string()));
+            }
+
+            let source = Box::into_raw(Box::new(source));
+            let avio = ffi::avio_alloc_context(
+                buffer,
+                AVIO_BUFFER_SIZE as c_int,
+                0,
+                source as *mut c_void,
+                Some(read_packet),
+                None,
+                Some(seek),
+            );
+            if avio.is_null() {
+                ffi::av_free(buffer as *mut c_void);
+                drop(Box::from_raw(source));
+                return Err(Error::VideoReadError("ffmpeg could not allocate an IO context".to_string()));
+            }
+
+            Ok(Self { avio, source })
+        }
+    }
+}
+
+impl Drop for AvioContext {
+    fn drop(&mut self) {
+        unsafe {
+            // ffmpeg may have replaced the buffer, so it's freed through the context rather than the original
+            ffi::av_freep(&mut (*self.avio).buffer as *mut *mut u8 as *mut c_void);
+            ffi::avio_context_free(&mut self.avio);
+            drop(Box::from_raw(self.source));
+        }
+    }
+}
+
+unsafe extern "C" fn read_packet(opaque: *mut c_void, buf: *mut u8, buf_size: c_int) -> c_int {
+    let source = unsafe { &mut *(opaque as *mut Box<dyn MediaSource>) };
+    let buf = unsafe { std::slice::from_raw_parts_mut(buf, buf_size as usize) };
+    match source.read(buf) {
+        Ok(0) => ffi::AVERROR_EOF,
+        Ok(read) => read as c_int,
+        Err(e) => e.raw_os_error().map(ffi::AVERROR).unwrap_or(ffi::AVERROR_EXTERNAL),
+    }
+}
+
+unsafe extern "C" fn seek(opaque: *mut c_void, offset: i64, whence: c_int) -> i64 {
+    let source = unsafe { &mut *(opaque as *mut Box<dyn MediaSource>) };
+    // AVSEEK_FORCE only hints that seeking should be done even if it's slow
+    let result = match whence & !(ffi::AVSEEK_FORCE as c_int) {
+        whence if whence == ffi::AVSEEK_SIZE as c_int => source_len(&mut **source),
+        0 => source.seek(SeekFrom::Start(offset as u64)),
+        1 => source.seek(SeekFrom::Current(offset)),
+        2 => source.seek(SeekFrom::End(offset)),
+        _ => return ffi::AVERROR_EXTERNAL as i64,
+    };
+    result.map(|position| position as i64).unwrap_or(ffi::AVERROR_EXTERNAL as i64)
+}
+
+/// Length of the source, restoring its position afterward
+fn source_len(source: &mut dyn MediaSource) -> std::io::Result<u64> {
+    let position = source.stream_position()?;
+    let len = source.seek(SeekFrom::End(0))?;
+    source.seek(SeekFrom::Start(position))?;
+    Ok(len)
+}
+
+/// Opens `source` as an ffmpeg input.  The format is probed from its contents, unless a `format` hint is
+/// given as an ffmpeg demuxer name such as `mp4`, `matroska` or `mpegts`.
+pub(crate) fn open_input(source: Box<dyn MediaSource>, format: Option<&str>) -> VideoResult<(Input, AvioContext)> {
+    let io = AvioContext::new(source)?;
+
+    unsafe {
+        let input_format = match format {
+            Some(format) => {
+                let name = CString::new(format)
+                    .map_err(|_| Error::VideoReadError(format!("Invalid ffmpeg input format {format}")))?;
+                let input_format = ffi::av_find_input_format(name.as_ptr());
+                if input_format.is_null() {
+                    return Err(Error::VideoReadError(format!("Unknown ffmpeg input format {format}")));
+                }
+                input_format
+            }
+            None => ptr::null(),
+        };
+
+        let mut context = ffi::avformat_alloc_context();
+        if context.is_null() {
+            return Err(Error::VideoReadError("ffmpeg could not allocate an input context".to_string()));
+        }
+        (*context).pb = io.avio;
+        (*context).flags |= ffi::AVFMT_FLAG_CUSTOM_IO as c_int;
+
+        // The context is freed by ffmpeg if opening fails
+        let res = ffi::avformat_open_input(&mut context, ptr::null(), input_format, ptr::null_mut());
+        if res < 0 {
+            return Err(Error::VideoReadError(format!("ffmpeg input error: {}", ffmpeg_next::Error::from(res))));
+        }
+
+        let res = ffi::avformat_find_stream_info(context, ptr::null_mut());
+        if res < 0 {
+            ffi::avformat_close_input(&mut context);
+            return Err(Error::VideoReadError(format!("ffmpeg input error: {}", ffmpeg_next::Error::from(res))));
+        }
+
+        Ok((Input::wrap(context), io))
+    }
+}
@@ -0,0 +1,53 @@
+use std::io::{BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::ascii::AsciiFrame;
+use crate::util::stdio::create_output;
+use crate::video::VideoResult;
+use crate::video::errors::Error;
+
+/// Clears the terminal and moves the cursor home before each frame is drawn
+const CLEAR_AND_HOME: &str = "\x1b[2J\x1b[H";
+
+/// Writes ascii frames to an [asciinema v2](https://docs.asciinema.org/manual/asciicast/v2/) `.cast`
+/// file so they can be replayed with `asciinema play` or embedded with asciinema-player.
+pub struct CastWriter {
+    writer: BufWriter<Box<dyn Write + Send>>,
+}
+
+impl CastWriter {
+    /// Creates the cast file, or writes to stdout if `path` is `-`, and writes the header, sizing the
+    /// terminal to fit `first_frame`
+    pub fn new(path: &str, first_frame: &AsciiFrame) -> VideoResult<Self> {
+        let file = create_output(path)
+            .map_err(|e| Error::VideoWriteError(format!("Could not create cast file at {path}: {e}")))?;
+        let mut writer = BufWriter::new(file);
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let header = json!({
+            "version": 2,
+            "width": first_frame.width,
+            "height": first_frame.height,
+            "timestamp": timestamp,
+            "env": { "TERM": "xterm-256color" },
+        });
+        writeln!(writer, "{header}")
+            .map_err(|e| Error::VideoWriteError(format!("Could not write cast header: {e}")))?;
+
+        Ok(Self { writer })
+    }
+
+    /// Appends a frame as an output event at `time` seconds from the start of the recording
+    pub fn write_frame(&mut self, time: f64, ascii: &AsciiFrame) -> VideoResult<()> {
+        let data = format!("{CLEAR_AND_HOME}{}", ascii.to_text("\r\n"));
+
+        let event = json!([time, "o", data]);
+        writeln!(self.writer, "{event}").map_err(|e| Error::VideoWriteError(format!("Could not write cast frame: {e}")))
+    }
+
+    pub fn close(&mut self) -> VideoResult<()> {
+        self.writer.flush().map_err(|e| Error::VideoWriteError(format!("Could not flush cast file: {e}")))
+    }
+}
@@ -11,6 +11,9 @@ pub enum Error {
     VideoWriteError(String),
     #[error("Operation cancelled")]
     Cancelled,
+    /// Requested functionality that mediatoascii was built without, by the name of its Cargo feature
+    #[error("{0} support is not enabled, rebuild mediatoascii with the `{0}` feature")]
+    FeatureDisabled(&'static str),
 }
 
 /// Manually implement Serialize to work with tauri
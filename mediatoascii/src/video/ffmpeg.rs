@@ -1,13 +1,26 @@
+use std::ffi::CStr;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
 use ffmpeg_next::codec::Id;
-use ffmpeg_next::format::{input, output, Pixel};
+use ffmpeg_next::format::context::Output;
+use ffmpeg_next::format::{input, input_with_dictionary, open_with, output, output_as, Pixel};
 use ffmpeg_next::media::Type;
 use ffmpeg_next::software::scaling::{context::Context, flag::Flags};
 use ffmpeg_next::util::frame::video::Video as FfmpegVideoFrame;
-use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use ffmpeg_next::{Dictionary, Format, Rational, Rescale, rescale};
+use tracing::{debug, error};
 
+use crate::ascii::{AsciiFrame, AsciiOptions};
 use crate::image::generate_ascii_image;
-use crate::util::constants::{GREYSCALE_RAMP, REVERSE_GREYSCALE_RAMP, RGB_TO_GREYSCALE};
+use crate::util::file_util::{is_stream_url, url_scheme};
+use crate::util::stdio::{PeekedStdin, is_stdio};
 use crate::util::{get_size_from_ascii, FFmpegFrame};
+use crate::video::avio::{open_input, AvioContext};
+use crate::video::live::{
+    is_live_output, live_output_format, HLS_FORMAT, HLS_PLAYLIST_SIZE, HLS_SEGMENT_SECONDS,
+    LIVE_KEYFRAME_INTERVAL_SECONDS,
+};
 use crate::video::encoder::Encoder;
 use crate::video::errors::Error;
 use crate::video::reader::Reader;
@@ -15,10 +28,23 @@ use crate::video::writer::Writer;
 use crate::video::VideoConfig;
 use crate::video::VideoResult;
 
+/// ffmpeg input device used for live capture on this platform
+#[cfg(target_os = "macos")]
+const CAPTURE_INPUT_FORMAT: &CStr = c"avfoundation";
+#[cfg(target_os = "windows")]
+const CAPTURE_INPUT_FORMAT: &CStr = c"dshow";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const CAPTURE_INPUT_FORMAT: &CStr = c"v4l2";
+
 /// We scale the time base and frame index as low values seem to skew ffmpeg's internal timestamp
 /// calculations and cause weird things like make a 2 second 30fps video output 15360 FPS for 4ms
 const TIME_BASE_SCALE: i32 = 1000;
 
+/// ffmpeg URL of stdout, for video outputs written to a pipe
+const STDOUT_URL: &str = "pipe:1";
+/// Container of video outputs written to stdout when no output format is configured
+const DEFAULT_STDOUT_FORMAT: &str = "mp4";
+
 pub struct FFmpegVideoReader {
     pub context: ffmpeg_next::format::context::Input,
     pub video_stream_index: usize,
@@ -27,21 +53,104 @@ pub struct FFmpegVideoReader {
     frames: Vec<FFmpegFrame>,
     decoder: ffmpeg_next::codec::decoder::video::Video,
     scaler: Context,
+    time_base: Rational,
+    start_time: i64,
+    /// After seeking, decoded frames before this timestamp are skipped as the demuxer seeks to keyframes
+    skip_until_pts: Option<i64>,
+    /// Whether the end of the input was sent to the decoder, after which it returns the frames it buffered
+    eof_sent: bool,
+    /// Custom IO the context reads through, for inputs that aren't read from a path.  Declared after the
+    /// context so it is dropped after it.
+    io: Option<AvioContext>,
 }
 
 impl FFmpegVideoReader {
+    /// Opens the video at `path`, which can also be the URL of a network stream, such as an http(s) video,
+    /// an HLS playlist or an RTSP stream
     pub fn new(path: &str) -> VideoResult<Self> {
         ffmpeg_next::init().map_err(|e| Error::VideoReadError(format!("ffmpeg init error: {e}")))?;
 
-        let context = input(path).map_err(|e| Error::VideoReadError(format!("ffmpeg input error: {e}")))?;
+        let context = if is_stream_url(path) {
+            ffmpeg_next::format::network::init();
+            input_with_dictionary(path, stream_options(path))
+        } else {
+            input(path)
+        }
+        .map_err(|e| Error::VideoReadError(format!("ffmpeg input error: {e}")))?;
+        Self::from_context(context)
+    }
+
+    /// Reads a video piped to stdin, after the bytes of it that were already read in `stdin`.  The container
+    /// is probed from its contents unless a `format` hint, such as `matroska` or `mpegts`, is given.
+    ///
+    /// stdin is read until it is closed before decoding, so that the input is seekable for containers
+    /// that need it, like mp4s that aren't fragmented.
+    pub fn from_stdin(stdin: PeekedStdin, format: Option<&str>) -> VideoResult<Self> {
+        ffmpeg_next::init().map_err(|e| Error::VideoReadError(format!("ffmpeg init error: {e}")))?;
+
+        let data =
+            stdin.read_to_end().map_err(|e| Error::VideoReadError(format!("Could not read video from stdin: {e}")))?;
+        Self::from_reader(Cursor::new(data), format)
+    }
+
+    /// Reads a video from memory, or any other seekable reader, without touching the filesystem.  The
+    /// container is probed from its contents unless a `format` hint, such as `matroska` or `mp4`, is given.
+    pub fn from_reader<R: Read + Seek + Send + 'static>(source: R, format: Option<&str>) -> VideoResult<Self> {
+        ffmpeg_next::init().map_err(|e| Error::VideoReadError(format!("ffmpeg init error: {e}")))?;
+
+        let (context, io) = open_input(Box::new(source), format)?;
+        let mut reader = Self::from_context(context)?;
+        reader.io = Some(io);
+        Ok(reader)
+    }
+
+    /// Reads a video from bytes in memory, such as an upload, see [FFmpegVideoReader::from_reader]
+    pub fn from_bytes(data: Vec<u8>, format: Option<&str>) -> VideoResult<Self> {
+        Self::from_reader(Cursor::new(data), format)
+    }
 
+    /// Opens a live capture device, as an index or a device path such as `/dev/video0`, with the platform's
+    /// ffmpeg capture input device (v4l2 on Linux, avfoundation on macOS, or dshow on Windows).
+    ///
+    /// A regular video file can stand in for the device, e.g. for testing without a camera, and is read as
+    /// if it were live.
+    pub fn from_device(device: &str) -> VideoResult<Self> {
+        ffmpeg_next::init().map_err(|e| Error::VideoReadError(format!("ffmpeg init error: {e}")))?;
+
+        let context = if Path::new(device).is_file() {
+            input(device).map_err(|e| Error::VideoReadError(format!("ffmpeg input error: {e}")))?
+        } else {
+            ffmpeg_next::device::register_all();
+            // SAFETY: the name is a valid C string, and a null result is checked before the format is used
+            let format = unsafe { ffmpeg_next::ffi::av_find_input_format(CAPTURE_INPUT_FORMAT.as_ptr()) };
+            if format.is_null() {
+                return Err(Error::VideoReadError(format!(
+                    "ffmpeg was built without the {} capture device",
+                    CAPTURE_INPUT_FORMAT.to_string_lossy()
+                )));
+            }
+            let format = Format::Input(unsafe { ffmpeg_next::format::Input::wrap(format as *mut _) });
+
+            open_with(&capture_device_url(device), &format, Dictionary::new())
+                .map_err(|e| Error::VideoReadError(format!("Could not open capture device {device}: {e}")))?
+                .input()
+        };
+
+        let mut reader = Self::from_context(context)?;
+        reader.total_frames = u64::MAX;
+        Ok(reader)
+    }
+
+    fn from_context(context: ffmpeg_next::format::context::Input) -> VideoResult<Self> {
         let video_stream = context
             .streams()
             .best(Type::Video)
             .ok_or_else(|| Error::VideoReadError("ffmpeg error: no video stream found".to_string()))?;
         let video_stream_index = video_stream.index();
 
-        let total_frames = video_stream.frames() as u64;
+        let time_base = video_stream.time_base();
+        let start_time =
+            if video_stream.start_time() == ffmpeg_next::ffi::AV_NOPTS_VALUE { 0 } else { video_stream.start_time() };
 
         let video_fps = video_stream.avg_frame_rate();
         let fps = if video_fps.denominator() != 0 {
@@ -50,6 +159,19 @@ impl FFmpegVideoReader {
             0.0
         };
 
+        let total_frames = if video_stream.frames() > 0 {
+            video_stream.frames() as u64
+        } else if video_stream.duration() > 0 {
+            // Some containers, like Matroska and MPEG-TS, don't store a frame count, so it's estimated from the
+            // duration, and corrected by finish() once every frame is read
+            (video_stream.duration() as f64 * f64::from(time_base) * fps).round() as u64
+        } else if context.duration() > 0 {
+            (context.duration() as f64 / ffmpeg_next::ffi::AV_TIME_BASE as f64 * fps).round() as u64
+        } else {
+            // Live streams have no duration, so are read until they end
+            u64::MAX
+        };
+
         let context_decoder = ffmpeg_next::codec::context::Context::from_parameters(video_stream.parameters())
             .map_err(|e| Error::VideoReadError(format!("ffmpeg codec context error: {e}")))?;
         let decoder = context_decoder
@@ -75,23 +197,42 @@ impl FFmpegVideoReader {
             video_stream_index,
             total_frames,
             fps,
-            frames: Vec::with_capacity(total_frames as usize),
+            frames: if total_frames == u64::MAX { vec![] } else { Vec::with_capacity(total_frames as usize) },
             decoder,
             scaler,
+            time_base,
+            start_time,
+            skip_until_pts: None,
+            eof_sent: false,
+            io: None,
         })
     }
 
     fn read_single_frame(&mut self) -> VideoResult<FFmpegFrame> {
         let mut decoded = ffmpeg_next::util::frame::video::Video::empty();
+        if !self.decode_next_frame(&mut decoded)? {
+            return Ok(FFmpegFrame::default());
+        }
 
+        let mut rgb_frame = ffmpeg_next::util::frame::video::Video::empty();
+        self.scaler
+            .run(&decoded, &mut rgb_frame)
+            .map_err(|e| Error::VideoReadError(format!("ffmpeg scaler run error: {e}")))?;
+        Ok(FFmpegFrame::new(rgb_frame))
+    }
+
+    /// Decodes the next frame into `decoded`, without converting its pixel format.  Returns false at EOF.
+    fn decode_next_frame(&mut self, decoded: &mut FfmpegVideoFrame) -> VideoResult<bool> {
         loop {
             // Try to receive a frame first
-            if self.decoder.receive_frame(&mut decoded).is_ok() {
-                let mut rgb_frame = ffmpeg_next::util::frame::video::Video::empty();
-                self.scaler
-                    .run(&decoded, &mut rgb_frame)
-                    .map_err(|e| Error::VideoReadError(format!("ffmpeg scaler run error: {e}")))?;
-                return Ok(FFmpegFrame::new(rgb_frame));
+            if self.decoder.receive_frame(decoded).is_ok() {
+                if let Some(skip_until_pts) = self.skip_until_pts {
+                    if decoded.timestamp().is_some_and(|pts| pts < skip_until_pts) {
+                        continue;
+                    }
+                    self.skip_until_pts = None;
+                }
+                return Ok(true);
             }
 
             // Otherwise, feed more packets
@@ -103,15 +244,77 @@ impl FFmpegVideoReader {
                             .map_err(|e| Error::VideoReadError(format!("send packet error: {e}")))?;
                     }
                 }
-                None => {
-                    // EOF, just continue until caller calls finish()
-                    return Ok(FFmpegFrame::default());
+                None if !self.eof_sent => {
+                    // Drain the frames the decoder is still holding on to
+                    self.decoder
+                        .send_eof()
+                        .map_err(|e| Error::VideoReadError(format!("ffmpeg send eof error: {e}")))?;
+                    self.eof_sent = true;
                 }
+                None => return Ok(false),
             }
         }
     }
 }
 
+/// Opens the output at `path`, along with the options for its muxer.  Besides files, the output can be
+/// stdout, a live streaming URL, or an HLS playlist.
+fn open_output(config: &VideoConfig, path: &str) -> VideoResult<(Output, Dictionary<'static>)> {
+    let mut options = Dictionary::new();
+    let output = if is_stdio(path) {
+        let format = config.output_format.as_deref().unwrap_or(DEFAULT_STDOUT_FORMAT);
+        if format == "mp4" || format == "mov" {
+            // A pipe can't be seeked back to write the index at the start, so mp4s are fragmented
+            options.set("movflags", "frag_keyframe+empty_moov+default_base_moof");
+        }
+        output_as(STDOUT_URL, format)
+    } else if let Some(format) = live_output_format(path) {
+        if format == HLS_FORMAT {
+            // Rolling segments next to the playlist, deleting the oldest as new ones are written
+            options.set("hls_time", &HLS_SEGMENT_SECONDS.to_string());
+            options.set("hls_list_size", &HLS_PLAYLIST_SIZE.to_string());
+            options.set("hls_flags", "delete_segments");
+        } else {
+            ffmpeg_next::format::network::init();
+        }
+        output_as(path, format)
+    } else {
+        output(path)
+    };
+
+    let output = output.map_err(|e| Error::VideoWriteError(format!("ffmpeg output creation error: {e}")))?;
+    Ok((output, options))
+}
+
+/// Options for opening network streams
+fn stream_options(url: &str) -> Dictionary<'static> {
+    let mut options = Dictionary::new();
+    match url_scheme(url).as_deref() {
+        Some("rtsp" | "rtsps") => {
+            // Interleaved in the RTSP connection, so frames aren't lost with dropped UDP packets
+            options.set("rtsp_transport", "tcp");
+        }
+        Some("http" | "https") => {
+            // Resumes http(s) videos and HLS segments if the connection drops
+            options.set("reconnect", "1");
+            options.set("reconnect_streamed", "1");
+        }
+        _ => {}
+    }
+    options
+}
+
+/// Maps a capture device index to the device name expected by the platform's capture input device
+fn capture_device_url(device: &str) -> String {
+    if cfg!(target_os = "windows") && !device.starts_with("video=") {
+        format!("video={device}")
+    } else if cfg!(not(any(target_os = "macos", target_os = "windows"))) && device.parse::<u32>().is_ok() {
+        format!("/dev/video{device}")
+    } else {
+        device.to_string()
+    }
+}
+
 impl Reader for FFmpegVideoReader {
     fn total_frames(&self) -> u64 {
         self.total_frames
@@ -121,37 +324,63 @@ impl Reader for FFmpegVideoReader {
         self.fps
     }
 
-    fn read_frame(&mut self, _config: &VideoConfig) -> VideoResult<()> {
-        let frame = self.read_single_frame()?;
-        if !frame.is_empty() {
-            self.frames.push(frame);
+    fn frame_size(&self) -> (u32, u32) {
+        (self.decoder.width(), self.decoder.height())
+    }
+
+    fn seek(&mut self, frame_index: u64) -> VideoResult<()> {
+        if !self.fps.is_finite() || self.fps <= 0.0 || self.time_base.numerator() == 0 {
+            return Err(Error::VideoReadError("Can't seek in a video without a known frame rate".to_string()));
         }
+        let target_pts = self.start_time + (frame_index as f64 / self.fps / f64::from(self.time_base)) as i64;
+        let timestamp = target_pts.rescale(self.time_base, rescale::TIME_BASE);
+        // Seeks to the closest keyframe before the timestamp, then decodes up to the exact frame
+        self.context
+            .seek(timestamp, ..timestamp)
+            .map_err(|e| Error::VideoReadError(format!("ffmpeg seek error: {e}")))?;
+        self.decoder.flush();
+        self.eof_sent = false;
+        self.skip_until_pts = Some(target_pts);
         Ok(())
     }
 
-    fn read_frame_as_ascii(&mut self, config: &VideoConfig) -> VideoResult<Vec<Vec<&str>>> {
+    fn skip_frame(&mut self) -> VideoResult<()> {
+        let mut decoded = ffmpeg_next::util::frame::video::Video::empty();
+        self.decode_next_frame(&mut decoded)?;
+        Ok(())
+    }
+
+    fn read_frame(&mut self, _config: &VideoConfig) -> VideoResult<bool> {
+        let frame = self.read_single_frame()?;
+        if frame.is_empty() {
+            return Ok(false);
+        }
+        self.frames.push(frame);
+        Ok(true)
+    }
+
+    fn read_frame_as_ascii(&mut self, config: &VideoConfig) -> VideoResult<Option<AsciiFrame>> {
         let frame = self.read_single_frame()?;
-        Ok(convert_ffmpeg_video_to_ascii(&frame, &config))
+        Ok((!frame.is_empty()).then(|| convert_ffmpeg_video_to_ascii(&frame, config)))
+    }
+
+    fn read_ascii_frame(&mut self, options: &AsciiOptions) -> VideoResult<Option<AsciiFrame>> {
+        let frame = self.read_single_frame()?;
+        Ok((!frame.is_empty()).then(|| frame.to_ascii(options)))
     }
 
     fn finish(&mut self) -> VideoResult<()> {
-        self.decoder.send_eof().map_err(|e| Error::VideoReadError(format!("ffmpeg send eof error: {e}")))?;
-        let mut decoded = ffmpeg_next::util::frame::video::Video::empty();
-        while self.decoder.receive_frame(&mut decoded).is_ok() {
-            let mut rgb_frame = ffmpeg_next::util::frame::video::Video::empty();
-            self.scaler
-                .run(&decoded, &mut rgb_frame)
-                .map_err(|e| Error::VideoReadError(format!("ffmpeg scaler run error: {e}")))?;
-            self.frames.push(FFmpegFrame::new(rgb_frame));
-            decoded = ffmpeg_next::util::frame::video::Video::empty();
-        }
+        // The decoder is drained as frames are read, so nothing is left to read here when reading stops early at
+        // the end of a time range.  The container's frame count may be missing or estimated, so it's replaced
+        // with the frames actually read.
+        self.total_frames = self.frames.len() as u64;
 
         Ok(())
     }
 }
 
 pub fn read_video_frames_ffmpeg(
-    FFmpegVideoReader { mut context, video_stream_index, total_frames:_, fps: _, mut frames, mut decoder, mut scaler}: FFmpegVideoReader,
+    FFmpegVideoReader { mut context, video_stream_index, total_frames:_, fps: _, mut frames, mut decoder, mut scaler, ..}: FFmpegVideoReader,
 ) -> VideoResult<()> {
     for (stream, packet) in context.packets() {
         if stream.index() == video_stream_index {
@@ -200,10 +429,30 @@ pub struct FFmpegVideoWriter {
 
 impl FFmpegVideoWriter {
     pub fn new(config: &VideoConfig, reader: FFmpegVideoReader) -> VideoResult<Self> {
+        Self::from_frames(config, reader.frames, config.output_fps(reader.fps))
+    }
+
+    /// Creates a writer that converts already read `input_frames` to ascii, sized to fit the first frame
+    pub fn from_frames(config: &VideoConfig, input_frames: Vec<FFmpegFrame>, fps: f64) -> VideoResult<Self> {
+        let ascii = convert_ffmpeg_video_to_ascii(&input_frames[0], &config);
+        let (width, height) = get_size_from_ascii(&ascii, config.height_sample_scale, config.font_size);
+
+        let mut writer = Self::with_size(config, width, height, fps)?;
+        writer.frames.reserve(input_frames.len());
+        writer.input_frames = input_frames;
+        writer.encode_ascii_frame(config, &ascii)?;
+
+        Ok(writer)
+    }
+
+    /// Creates a writer for ascii frames that are rendered to `width` x `height` pixel frames, without
+    /// any input video frames.  Frames are added with [FFmpegVideoWriter::encode_ascii_frame].
+    pub fn with_size(config: &VideoConfig, width: u32, height: u32, fps: f64) -> VideoResult<Self> {
         ffmpeg_next::init().map_err(|e| Error::VideoWriteError(format!("ffmpeg init error: {e}")))?;
 
-        let mut output = output(config.output_video_path.as_ref().unwrap())
-            .map_err(|e| Error::VideoWriteError(format!("ffmpeg output creation error: {e}")))?;
+        let output_path = config.output_video_path.as_ref().unwrap();
+        let live = is_live_output(output_path);
+        let (mut output, options) = open_output(config, output_path)?;
 
         let codec = ffmpeg_next::codec::encoder::find(Id::H264)
             .ok_or_else(|| Error::VideoWriteError("ffmpeg error: H264 codec not found".to_string()))?;
@@ -215,10 +464,6 @@ impl FFmpegVideoWriter {
             .video()
             .map_err(|e| Error::VideoWriteError(format!("ffmpeg encoder error: {e}")))?;
 
-        let mut frames = Vec::with_capacity(reader.total_frames as usize);
-        let input_frames = reader.frames;
-        let ascii = convert_ffmpeg_video_to_ascii(&input_frames[0], &config);
-        let (width, height) = get_size_from_ascii(&ascii, config.height_sample_scale, config.font_size);
         // ffmpeg for h264 requires width/height to be divisible by 2
         let width = if width % 2 == 0 { width } else { width + 1 };
         let height = if height % 2 == 0 { height } else { height + 1 };
@@ -228,25 +473,35 @@ impl FFmpegVideoWriter {
         //     return Err(Error::ResolutionTooLarge);
         // }
 
-        let time_base = ffmpeg_next::Rational::new(1, reader.fps as i32 * TIME_BASE_SCALE);
-        println!("fps: {}, time_base: {}/{}", reader.fps, time_base.numerator(), time_base.denominator());
+        let frame_rate = output_frame_rate(fps)?;
+        let time_base = output_time_base(frame_rate);
+        debug!("fps: {}, time_base: {}/{}", fps, time_base.numerator(), time_base.denominator());
 
         video_encoder.set_width(width);
         video_encoder.set_height(height);
         video_encoder.set_format(Pixel::YUV420P);
-        video_encoder.set_frame_rate(Some(ffmpeg_next::Rational::new(reader.fps as i32, 1)));
+        video_encoder.set_frame_rate(Some(frame_rate));
         video_encoder.set_time_base(time_base);
         if let Some(bitrate) = config.bitrate {
             video_encoder.set_bit_rate(bitrate as usize);
         }
-        video_encoder.set_gop(250);
+        // Live outputs need frequent keyframes, as viewers can only start watching, and HLS segments can
+        // only be cut, at a keyframe
+        video_encoder.set_gop(if live { (fps * LIVE_KEYFRAME_INTERVAL_SECONDS).round().max(1.0) as u32 } else { 250 });
 
         if output.format().flags().contains(ffmpeg_next::format::flag::Flags::GLOBAL_HEADER) {
             video_encoder.set_flags(ffmpeg_next::codec::flag::Flags::GLOBAL_HEADER);
         }
 
-        let encoder =
-            video_encoder.open().map_err(|e| Error::VideoWriteError(format!("ffmpeg encoder open error: {e:?}")))?;
+        let encoder = if live {
+            let mut encoder_options = Dictionary::new();
+            encoder_options.set("preset", "veryfast");
+            encoder_options.set("tune", "zerolatency");
+            video_encoder.open_with(encoder_options)
+        } else {
+            video_encoder.open()
+        }
+        .map_err(|e| Error::VideoWriteError(format!("ffmpeg encoder open error: {e:?}")))?;
 
         let mut stream =
             output.add_stream(codec).map_err(|e| Error::VideoWriteError(format!("ffmpeg add stream error: {e}")))?;
@@ -258,12 +513,12 @@ impl FFmpegVideoWriter {
 
         // Flag has no effect here since we're not scaling resolution (only converting color format),
         // but is required by the API
-        let mut scaler = Context::get(Pixel::RGB24, width, height, Pixel::YUV420P, width, height, Flags::BILINEAR)
+        let scaler = Context::get(Pixel::RGB24, width, height, Pixel::YUV420P, width, height, Flags::BILINEAR)
             .map_err(|e| Error::VideoWriteError(format!("ffmpeg scaler error: {e}")))?;
 
-        output.write_header().map_err(|e| Error::VideoWriteError(format!("ffmpeg write header error: {e}")))?;
-
-        frames.push(encode_ascii_frame_ffmpeg(&config, &ascii, width, height, 0, &mut scaler)?);
+        output
+            .write_header_with(options)
+            .map_err(|e| Error::VideoWriteError(format!("ffmpeg write header error: {e}")))?;
 
         Ok(Self {
             context: output,
@@ -271,15 +526,34 @@ impl FFmpegVideoWriter {
             stream_time_base,
             encoder,
             scaler,
-            input_frames,
+            input_frames: Vec::new(),
             width,
             height,
-            frames,
-            frame_index: 1,
+            frames: Vec::new(),
+            frame_index: 0,
             closed: false,
         })
     }
 
+    /// Renders and encodes an ascii frame as the next frame of the output video
+    pub fn encode_ascii_frame(&mut self, config: &VideoConfig, ascii: &AsciiFrame) -> VideoResult<()> {
+        let frame =
+            encode_ascii_frame_ffmpeg(config, ascii, self.width, self.height, self.frame_index, &mut self.scaler)?;
+        self.frame_index += 1;
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    /// Renders, encodes and writes an ascii frame right away, without keeping it, for live outputs that are
+    /// written as the input is read
+    pub fn write_ascii_frame(&mut self, config: &VideoConfig, ascii: &AsciiFrame) -> VideoResult<()> {
+        let frame =
+            encode_ascii_frame_ffmpeg(config, ascii, self.width, self.height, self.frame_index, &mut self.scaler)?;
+        self.frame_index += 1;
+        self.encoder.send_frame(&frame).map_err(|e| Error::VideoWriteError(format!("ffmpeg send frame error: {e}")))?;
+        self.flush_packets()
+    }
+
     fn flush_packets(&mut self) -> VideoResult<()> {
         let mut packet = ffmpeg_next::codec::packet::Packet::empty();
         while self.encoder.receive_packet(&mut packet).is_ok() {
@@ -297,11 +571,7 @@ impl FFmpegVideoWriter {
 impl Encoder for FFmpegVideoWriter {
     fn encode_frame(&mut self, config: &VideoConfig, frame_index: usize) -> VideoResult<()> {
         let ascii = convert_ffmpeg_video_to_ascii(&self.input_frames[frame_index], &config);
-        let frame =
-            encode_ascii_frame_ffmpeg(&config, &ascii, self.width, self.height, self.frame_index, &mut self.scaler)?;
-        self.frame_index += 1;
-        self.frames.push(frame);
-        Ok(())
+        self.encode_ascii_frame(config, &ascii)
     }
 }
 
@@ -335,45 +605,42 @@ impl Writer for FFmpegVideoWriter {
 impl Drop for FFmpegVideoWriter {
     fn drop(&mut self) {
         if let Err(e) = self.close() {
-            eprintln!("FFmpegVideoWriter close error: {e}");
+            error!("FFmpegVideoWriter close error: {e}");
         }
     }
 }
 
-/// Converts a ffmpeg frame into ascii representation 2-d Vector
-#[inline]
-pub fn convert_ffmpeg_video_to_ascii(frame: &FFmpegFrame, config: &VideoConfig) -> Vec<Vec<&'static str>> {
-    let scale_down = config.scale_down;
-    let height_sample_scale = config.height_sample_scale;
-
-    let width = frame.width;
-    let height = frame.height;
-
-    let scaled_width = (width as f32 / scale_down) as usize;
-    let scaled_height = ((height as f32 / scale_down) / height_sample_scale) as usize;
-    let mut res = vec![vec![" "; scaled_width]; scaled_height];
+/// Largest numerator or denominator of an output frame rate, small enough that the numerator scaled by
+/// [TIME_BASE_SCALE] still fits the time base
+const MAX_FRAME_RATE_TERM: i32 = 100_000;
 
-    let greyscale_ramp: &[&str] = if config.invert { &REVERSE_GREYSCALE_RAMP } else { &GREYSCALE_RAMP };
-
-    res.par_iter_mut().enumerate().for_each(|(y, row)| {
-        (0..scaled_width).for_each(|x| {
-            let src_y = (y as f32 * scale_down * height_sample_scale) as u32;
-            let src_x = (x as f32 * scale_down) as u32;
+/// Frame rate of an output video as a rational, so fractional rates like 30 fps with every 4th frame (7.5 fps)
+/// aren't truncated, which would stretch or squash the video
+fn output_frame_rate(fps: f64) -> VideoResult<Rational> {
+    if !fps.is_finite() || fps <= 0.0 {
+        return Err(Error::VideoWriteError(format!("Invalid output frame rate: {fps} fps")));
+    }
+    let frame_rate = Rational::from(unsafe { ffmpeg_next::ffi::av_d2q(fps, MAX_FRAME_RATE_TERM) });
+    if frame_rate.numerator() <= 0 {
+        return Err(Error::VideoWriteError(format!("Output frame rate is too low: {fps} fps")));
+    }
+    Ok(frame_rate)
+}
 
-            let (r, g, b) = frame.get_pixel(src_x, src_y);
-            let greyscale_value =
-                RGB_TO_GREYSCALE.0 * r as f32 + RGB_TO_GREYSCALE.1 * g as f32 + RGB_TO_GREYSCALE.2 * b as f32;
-            let index = (greyscale_value * (greyscale_ramp.len() - 1) as f32 / 255.0).ceil() as usize;
-            row[x] = greyscale_ramp[index];
-        })
-    });
+/// Time base of an output video, with [TIME_BASE_SCALE] ticks per frame
+fn output_time_base(frame_rate: Rational) -> Rational {
+    Rational::new(frame_rate.denominator(), frame_rate.numerator() * TIME_BASE_SCALE)
+}
 
-    res
+/// Converts a ffmpeg frame into ascii, keeping the source color of each cell if `config.export_colors` is set
+#[inline]
+pub fn convert_ffmpeg_video_to_ascii(frame: &FFmpegFrame, config: &VideoConfig) -> AsciiFrame {
+    frame.to_ascii(&config.into())
 }
 
 pub fn encode_ascii_frame_ffmpeg(
     config: &VideoConfig,
-    ascii: &[Vec<&str>],
+    ascii: &AsciiFrame,
     width: u32,
     height: u32,
     frame_index: i64,
@@ -403,3 +670,50 @@ pub fn encode_ascii_frame_ffmpeg(
 
     Ok(yuv_frame)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video::VideoConfigBuilder;
+
+    /// Duration in seconds of `frames` output frames, as a `(numerator, denominator)` pair
+    fn duration(frame_rate: Rational, frames: i64) -> (i64, i64) {
+        let time_base = output_time_base(frame_rate);
+        (frames * TIME_BASE_SCALE as i64 * time_base.numerator() as i64, time_base.denominator() as i64)
+    }
+
+    #[test]
+    fn every_nth_frame_keeps_input_duration() {
+        // 4 seconds of 30 fps video, keeping every 4th frame
+        let config = VideoConfigBuilder::default().every_nth_frame(4).build().unwrap();
+        let frame_rate = output_frame_rate(config.output_fps(30.0)).unwrap();
+        assert_eq!((frame_rate.numerator(), frame_rate.denominator()), (15, 2));
+
+        let (seconds, per) = duration(frame_rate, 120 / 4);
+        assert_eq!(seconds, 4 * per);
+    }
+
+    #[test]
+    fn every_nth_frame_above_fps() {
+        // 10 seconds of 2 fps video, keeping every 5th frame
+        let config = VideoConfigBuilder::default().every_nth_frame(5).build().unwrap();
+        let frame_rate = output_frame_rate(config.output_fps(2.0)).unwrap();
+        assert_eq!((frame_rate.numerator(), frame_rate.denominator()), (2, 5));
+
+        let (seconds, per) = duration(frame_rate, 20 / 5);
+        assert_eq!(seconds, 10 * per);
+    }
+
+    #[test]
+    fn ntsc_frame_rate() {
+        let frame_rate = output_frame_rate(30000.0 / 1001.0).unwrap();
+        assert_eq!((frame_rate.numerator(), frame_rate.denominator()), (30000, 1001));
+    }
+
+    #[test]
+    fn rejects_invalid_frame_rates() {
+        for fps in [0.0, -30.0, f64::NAN, f64::INFINITY, 1e-9] {
+            assert!(matches!(output_frame_rate(fps), Err(Error::VideoWriteError(_))), "{fps} fps");
+        }
+    }
+}
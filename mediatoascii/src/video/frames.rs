@@ -0,0 +1,222 @@
+use std::ops::Deref;
+
+use crate::ascii::{AsciiFrame, AsciiOptions};
+use crate::video::ffmpeg::FFmpegVideoReader;
+use crate::video::range::FrameRange;
+use crate::video::reader::Reader;
+use crate::video::{VideoConfig, VideoResult};
+
+/// Ascii of a frame of a video, along with where the frame is in the video
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoAsciiFrame {
+    /// Index of the frame in the source video
+    pub index: u64,
+    /// Nominal timestamp in seconds, from the frame's index and the video's FPS.  This isn't the decoded
+    /// frame's presentation timestamp, so it drifts from it in videos with a variable frame rate.
+    pub nominal_timestamp: f64,
+    /// Width of the source frame, before any rotation
+    pub source_width: u32,
+    /// Height of the source frame, before any rotation
+    pub source_height: u32,
+    pub ascii: AsciiFrame,
+}
+
+impl Deref for VideoAsciiFrame {
+    type Target = AsciiFrame;
+
+    fn deref(&self) -> &Self::Target {
+        &self.ascii
+    }
+}
+
+/// Ascii of each frame of a video, decoded lazily as the iterator is advanced, e.g. to build custom players,
+/// analytics or exporters.  Iteration stops after the last frame, or after the first error.
+///
+/// Works over any [Reader], such as a [FFmpegVideoReader] opened from a path or from memory, or an
+/// `OpenCVVideoReader` with the `opencv` feature.
+pub struct AsciiFrames<R: Reader = FFmpegVideoReader> {
+    reader: R,
+    options: AsciiOptions,
+    range: FrameRange,
+    /// Number of frames read so far
+    read: usize,
+    finished: bool,
+}
+
+impl<R: Reader> AsciiFrames<R> {
+    /// Iterates over every frame of the video
+    pub fn new(reader: R, options: AsciiOptions) -> Self {
+        Self { reader, options, range: FrameRange { start: 0, end: None, step: 1 }, read: 0, finished: false }
+    }
+
+    /// Iterates over the frames selected by the `start`, `end`, `duration` and `every_nth_frame` settings of
+    /// `config`, converted with its ascii settings
+    pub fn from_config(reader: R, config: &VideoConfig) -> VideoResult<Self> {
+        let range = FrameRange::new(config, reader.fps(), reader.total_frames())?;
+        Ok(Self { range, ..Self::new(reader, config.into()) })
+    }
+
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    fn read_next(&mut self, index: u64) -> VideoResult<Option<VideoAsciiFrame>> {
+        self.range.advance(&mut self.reader, self.read)?;
+        let Some(ascii) = self.reader.read_ascii_frame(&self.options)? else {
+            return Ok(None);
+        };
+        self.read += 1;
+
+        let fps = self.reader.fps();
+        let (source_width, source_height) = self.reader.frame_size();
+        Ok(Some(VideoAsciiFrame {
+            index,
+            nominal_timestamp: if fps > 0.0 { index as f64 / fps } else { 0.0 },
+            source_width,
+            source_height,
+            ascii,
+        }))
+    }
+}
+
+impl<R: Reader> Iterator for AsciiFrames<R> {
+    type Item = VideoResult<VideoAsciiFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.range.start + self.read as u64 * self.range.step;
+        if self.finished || self.range.end.is_some_and(|end| index >= end) {
+            return None;
+        }
+        let frame = self.read_next(index).transpose();
+        self.finished = !matches!(frame, Some(Ok(_)));
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video::VideoConfigBuilder;
+    use crate::video::errors::Error;
+    use crate::video::range::TimePosition;
+
+    /// Reader of `frames` frames whose ascii is their index, which may report a different frame count
+    struct FakeReader {
+        frames: u64,
+        total_frames: u64,
+        position: u64,
+        fail_at: Option<u64>,
+    }
+
+    impl FakeReader {
+        fn new(frames: u64) -> Self {
+            Self { frames, total_frames: frames, position: 0, fail_at: None }
+        }
+    }
+
+    impl Reader for FakeReader {
+        fn total_frames(&self) -> u64 {
+            self.total_frames
+        }
+
+        fn fps(&self) -> f64 {
+            10.0
+        }
+
+        fn frame_size(&self) -> (u32, u32) {
+            (4, 2)
+        }
+
+        fn seek(&mut self, frame_index: u64) -> VideoResult<()> {
+            self.position = frame_index;
+            Ok(())
+        }
+
+        fn skip_frame(&mut self) -> VideoResult<()> {
+            self.position += 1;
+            Ok(())
+        }
+
+        fn read_frame(&mut self, _config: &VideoConfig) -> VideoResult<bool> {
+            unimplemented!()
+        }
+
+        fn read_frame_as_ascii(&mut self, _config: &VideoConfig) -> VideoResult<Option<AsciiFrame>> {
+            unimplemented!()
+        }
+
+        fn read_ascii_frame(&mut self, _options: &AsciiOptions) -> VideoResult<Option<AsciiFrame>> {
+            if self.fail_at == Some(self.position) {
+                return Err(Error::VideoReadError("corrupt frame".to_string()));
+            }
+            if self.position >= self.frames {
+                return Ok(None);
+            }
+            self.position += 1;
+            Ok(Some(AsciiFrame::from_text(&(self.position - 1).to_string())))
+        }
+
+        fn finish(&mut self) -> VideoResult<()> {
+            Ok(())
+        }
+    }
+
+    fn indices<R: Reader>(frames: AsciiFrames<R>) -> Vec<u64> {
+        frames.map(|frame| frame.unwrap().index).collect()
+    }
+
+    #[test]
+    fn iterates_every_frame() {
+        let frames: Vec<_> =
+            AsciiFrames::new(FakeReader::new(3), AsciiOptions::default()).map(Result::unwrap).collect();
+        assert_eq!(frames.len(), 3);
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(frame.index, i as u64);
+            assert_eq!(frame.to_string(), format!("{i}\n"));
+            assert_eq!(frame.nominal_timestamp, i as f64 / 10.0);
+            assert_eq!((frame.source_width, frame.source_height), (4, 2));
+        }
+    }
+
+    #[test]
+    fn iterates_frames_in_range() {
+        let config = VideoConfigBuilder::default()
+            .start(Some(TimePosition::Frame(2)))
+            .end(Some(TimePosition::Seconds(0.9)))
+            .every_nth_frame(3)
+            .build()
+            .unwrap();
+        let frames = AsciiFrames::from_config(FakeReader::new(20), &config).unwrap();
+        // Frames are read where they are in the source, not just numbered by their index
+        let ascii: Vec<_> = frames.map(|frame| frame.unwrap().ascii.to_string()).collect();
+        assert_eq!(ascii, ["2\n", "5\n", "8\n"]);
+
+        let config = VideoConfigBuilder::default().start(Some(TimePosition::Frame(20))).build().unwrap();
+        assert!(AsciiFrames::from_config(FakeReader::new(20), &config).is_err());
+    }
+
+    #[test]
+    fn stops_at_end_of_input() {
+        // Frame counts may be estimated, so reading stops at the last frame actually read
+        let reader = FakeReader { total_frames: 10, ..FakeReader::new(4) };
+        assert_eq!(indices(AsciiFrames::new(reader, AsciiOptions::default())), [0, 1, 2, 3]);
+
+        let reader = FakeReader { total_frames: u64::MAX, ..FakeReader::new(2) };
+        let mut frames = AsciiFrames::new(reader, AsciiOptions::default());
+        assert_eq!(frames.by_ref().count(), 2);
+        assert!(frames.next().is_none());
+    }
+
+    #[test]
+    fn stops_after_an_error() {
+        let reader = FakeReader { fail_at: Some(1), ..FakeReader::new(4) };
+        let mut frames = AsciiFrames::new(reader, AsciiOptions::default());
+        assert!(frames.next().unwrap().is_ok());
+        assert!(frames.next().unwrap().is_err());
+        assert!(frames.next().is_none());
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use signal_hook::SigId;
+use signal_hook::consts::SIGINT;
+
+use crate::video::VideoResult;
+use crate::video::errors::Error;
+
+/// Stops reading streams of unknown length on Ctrl-C, so the frames read so far are still written to the
+/// output instead of the process being killed
+pub(crate) struct StreamInterrupt {
+    interrupted: Arc<AtomicBool>,
+    sigint: Option<SigId>,
+}
+
+impl StreamInterrupt {
+    /// Registers the Ctrl-C handler if `enabled`, otherwise Ctrl-C is left to kill the process
+    pub(crate) fn register(enabled: bool) -> VideoResult<Self> {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let sigint = enabled
+            .then(|| signal_hook::flag::register(SIGINT, Arc::clone(&interrupted)))
+            .transpose()
+            .map_err(|e| Error::VideoReadError(format!("Could not register Ctrl-C handler: {e}")))?;
+        Ok(Self { interrupted, sigint })
+    }
+
+    pub(crate) fn interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for StreamInterrupt {
+    fn drop(&mut self) {
+        if let Some(sigint) = self.sigint {
+            signal_hook::low_level::unregister(sigint);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::Instant;
+
+use crate::util::progress::{ProgressEvent, ProgressObserver, Stage};
+use crate::video::errors::Error;
+use crate::video::{VideoConfig, VideoResult, process_video_job};
+
+/// Id of the next [VideoJob] created
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Cancels a video job from another thread.  Clones share the same cancellation, so a token can be handed
+/// to whatever should be able to cancel the job, such as a UI's cancel button.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation.  The job stops at the next frame with [Error::Cancelled].
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns [Error::Cancelled] if cancellation was requested
+    pub(crate) fn check(&self) -> VideoResult<()> {
+        if self.is_cancelled() { Err(Error::Cancelled) } else { Ok(()) }
+    }
+}
+
+/// Progress of a video job, updated by the job as it runs and readable from any thread.
+///
+/// Videos written to a file are read, then encoded, then written, so each stage has its own frame counter.
+#[derive(Debug, Default)]
+pub struct JobProgress {
+    /// Bits of the f32 percentage, as there is no atomic f32
+    percentage: AtomicU32,
+    read_current_frame: AtomicU64,
+    encode_current_frame: AtomicU64,
+    write_current_frame: AtomicU64,
+    total_frames: AtomicU64,
+}
+
+impl JobProgress {
+    /// Overall progress, from 0.0 to 1.0
+    pub fn percentage(&self) -> f32 {
+        f32::from_bits(self.percentage.load(Ordering::Relaxed))
+    }
+
+    /// Current frame being processed during read step
+    pub fn read_current_frame(&self) -> u64 {
+        self.read_current_frame.load(Ordering::Relaxed)
+    }
+
+    /// Current frame being processed during encode step
+    pub fn encode_current_frame(&self) -> u64 {
+        self.encode_current_frame.load(Ordering::Relaxed)
+    }
+
+    /// Current frame being processed during write step
+    pub fn write_current_frame(&self) -> u64 {
+        self.write_current_frame.load(Ordering::Relaxed)
+    }
+
+    /// Total number of frames in the video, or `u64::MAX` until streams of unknown length are read
+    pub fn total_frames(&self) -> u64 {
+        self.total_frames.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_percentage(&self, percentage: f32) {
+        self.percentage.store(percentage.to_bits(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_read_frame(&self) {
+        self.read_current_frame.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_encode_frame(&self) {
+        self.encode_current_frame.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn inc_write_frame(&self) {
+        self.write_current_frame.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_total_frames(&self, total_frames: u64) {
+        self.total_frames.store(total_frames, Ordering::Relaxed);
+    }
+}
+
+/// Handle to a video conversion, to follow its progress and cancel it from other threads.
+///
+/// Each job has its own progress and cancellation, so several jobs can run concurrently in one process.
+/// Clones share the same job, so a clone can be kept to poll progress while the job runs on another thread.
+/// Progress can also be pushed to a [ProgressObserver] with [VideoJob::with_observer].
+#[derive(Clone)]
+pub struct VideoJob {
+    id: u64,
+    progress: Arc<JobProgress>,
+    cancellation: CancellationToken,
+    observer: Option<Arc<dyn ProgressObserver>>,
+}
+
+impl VideoJob {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a job cancelled by `cancellation`, e.g. to cancel several jobs with one token
+    pub fn with_cancellation(cancellation: CancellationToken) -> Self {
+        Self { id: NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed), progress: Arc::default(), cancellation, observer: None }
+    }
+
+    /// Id of the job, unique within the process, which its log messages are tagged with
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Sends the job's progress events to `observer` as it runs
+    pub fn with_observer<O: ProgressObserver + 'static>(mut self, observer: O) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    pub fn progress(&self) -> &JobProgress {
+        &self.progress
+    }
+
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Requests cancellation of the job, see [CancellationToken::cancel]
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Runs the video conversion for `config` as this job, blocking until it finishes or is cancelled
+    pub fn run(&self, config: VideoConfig) -> VideoResult<()> {
+        process_video_job(config, self)
+    }
+}
+
+impl Default for VideoJob {
+    fn default() -> Self {
+        Self::with_cancellation(CancellationToken::default())
+    }
+}
+
+impl fmt::Debug for VideoJob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VideoJob")
+            .field("id", &self.id)
+            .field("progress", &self.progress)
+            .field("cancellation", &self.cancellation)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+/// Reports the progress of a running job to its [JobProgress] and observer
+pub(crate) struct JobReporter<'a> {
+    job: &'a VideoJob,
+    started: Instant,
+    /// Frames processed so far, counting each stage a frame goes through
+    steps: u64,
+    /// Frames to process across all stages, once known
+    total_steps: Option<u64>,
+}
+
+impl<'a> JobReporter<'a> {
+    pub(crate) fn new(job: &'a VideoJob) -> Self {
+        Self { job, started: Instant::now(), steps: 0, total_steps: None }
+    }
+
+    fn emit(&self, event: ProgressEvent) {
+        if let Some(observer) = self.job.observer.as_ref() {
+            observer.on_progress(&event);
+        }
+    }
+
+    /// Reports the opened input, whose `total_frames` is `u64::MAX` if unknown
+    pub(crate) fn probe_finished(&self, (width, height): (u32, u32), fps: f64, total_frames: u64) {
+        let total_frames = (total_frames != u64::MAX).then_some(total_frames);
+        self.emit(ProgressEvent::ProbeFinished { width, height, fps: Some(fps), total_frames });
+    }
+
+    /// Starts `stage`, processing `total_frames` frames, which is `u64::MAX` if unknown
+    pub(crate) fn stage(&self, stage: Stage, total_frames: u64) {
+        self.job.progress.set_total_frames(total_frames);
+        let total_frames = (total_frames != u64::MAX).then_some(total_frames);
+        self.emit(ProgressEvent::StageChanged { stage, total_frames });
+    }
+
+    /// Sets the frames to process across all stages, and how many were already processed, once known
+    pub(crate) fn set_steps(&mut self, steps: u64, total_steps: u64) {
+        self.steps = steps;
+        self.total_steps = (total_steps != u64::MAX).then_some(total_steps);
+    }
+
+    pub(crate) fn frame_read(&mut self, index: u64) {
+        self.job.progress.inc_read_frame();
+        self.emit(ProgressEvent::FrameRead { index });
+        self.step();
+    }
+
+    pub(crate) fn frame_encoded(&mut self, index: u64) {
+        self.job.progress.inc_encode_frame();
+        self.emit(ProgressEvent::FrameEncoded { index });
+        self.step();
+    }
+
+    pub(crate) fn frame_written(&mut self, index: u64) {
+        self.job.progress.inc_write_frame();
+        self.emit(ProgressEvent::FrameWritten { index });
+        self.step();
+    }
+
+    /// Updates the percentage and the estimated time left after a frame is processed
+    fn step(&mut self) {
+        self.steps += 1;
+        let Some(total_steps) = self.total_steps.filter(|total_steps| *total_steps > 0) else {
+            return;
+        };
+        let percentage = (self.steps as f32 / total_steps as f32).min(1.0);
+        self.job.progress.set_percentage(percentage);
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let remaining_seconds = elapsed * (1.0 - percentage as f64) / percentage as f64;
+        self.emit(ProgressEvent::Eta { percentage, remaining_seconds });
+    }
+
+    /// Reports the result of the job
+    pub(crate) fn finish(&self, result: &VideoResult<()>) {
+        match result {
+            Ok(()) => {
+                self.job.progress.set_percentage(1.0);
+                self.emit(ProgressEvent::Finished);
+            }
+            Err(e) => self.emit(ProgressEvent::Error { message: e.to_string() }),
+        }
+    }
+}
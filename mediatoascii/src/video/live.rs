@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::Path;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use tracing::info;
+
+use crate::util::file_util::{is_stream_url, url_scheme};
+use crate::util::get_size_from_ascii;
+use crate::util::progress::Stage;
+use crate::video::errors::Error;
+use crate::video::interrupt::StreamInterrupt;
+use crate::video::job::JobReporter;
+use crate::video::range::FrameRange;
+use crate::video::reader::{FALLBACK_FPS, Reader};
+use crate::video::writer::Writer;
+use crate::video::{FFmpegVideoWriter, VideoConfig, VideoResult};
+
+/// ffmpeg muxer that writes HLS playlists and their segments
+pub(crate) const HLS_FORMAT: &str = "hls";
+/// Target duration of each HLS segment
+pub(crate) const HLS_SEGMENT_SECONDS: u32 = 2;
+/// Number of segments listed in the rolling HLS playlist, older segments are deleted
+pub(crate) const HLS_PLAYLIST_SIZE: u32 = 6;
+/// Seconds between keyframes of live outputs, matching the HLS segment duration
+pub(crate) const LIVE_KEYFRAME_INTERVAL_SECONDS: f64 = HLS_SEGMENT_SECONDS as f64;
+
+/// ffmpeg muxer for a live output at `path`: flv for RTMP URLs, MPEG-TS for SRT and UDP URLs, or HLS for
+/// `.m3u8` playlists.  Returns `None` if `path` isn't a live output.
+pub fn live_output_format(path: &str) -> Option<&'static str> {
+    match url_scheme(path).as_deref() {
+        Some("rtmp" | "rtmps") => Some("flv"),
+        Some("srt" | "udp") => Some("mpegts"),
+        _ if Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("m3u8")) => Some(HLS_FORMAT),
+        _ => None,
+    }
+}
+
+/// Whether `path` is a live output, i.e. an RTMP, SRT or UDP URL to push the stream to, or an HLS playlist
+/// to write rolling segments for.
+///
+/// RTMP, SRT and UDP URLs are also [stream URLs](is_stream_url), as ffmpeg can both read from and push to
+/// them.  Which one a URL is depends only on where it's given: as the input it's read, as the output video
+/// it's pushed to.
+pub fn is_live_output(path: &str) -> bool {
+    live_output_format(path).is_some()
+}
+
+/// Streams the ascii frames of `reader` to the live output at `config.output_video_path`.
+///
+/// Each frame is rendered, encoded and written as it is read, rather than after the whole input is read,
+/// and frames are written in real time so viewers receive them at the input's frame rate.  Streaming stops
+/// at the end of the input or its time range, or when Ctrl-C is pressed, so sources of unknown length can be
+/// streamed.
+pub(crate) fn stream_live<R: Reader>(
+    reader: &mut R,
+    config: &VideoConfig,
+    reporter: &mut JobReporter,
+) -> VideoResult<()> {
+    let output_path = config.output_video_path.as_deref().unwrap();
+    if !is_stream_url(output_path)
+        && let Some(dir) = Path::new(output_path).parent().filter(|dir| !dir.as_os_str().is_empty())
+    {
+        fs::create_dir_all(dir).map_err(|e| {
+            Error::VideoWriteError(format!("Could not create HLS output directory {}: {e}", dir.display()))
+        })?;
+    }
+
+    let fps = if reader.fps() > 0.0 { reader.fps() } else { FALLBACK_FPS };
+    let range = FrameRange::new(config, fps, reader.total_frames())?;
+    let num_frames = range.len(reader.total_frames());
+    reporter.stage(Stage::Converting, num_frames);
+    reporter.set_steps(0, num_frames);
+    let interrupt = StreamInterrupt::register(true)?;
+    info!("Streaming ascii video to {output_path}, press Ctrl-C to stop");
+
+    // Output dimensions come from the first frame, so the writer is created lazily
+    let mut writer: Option<FFmpegVideoWriter> = None;
+    let start = Instant::now();
+    for (n, i) in range.frame_indices().enumerate() {
+        if interrupt.interrupted() {
+            break;
+        }
+        range.advance(reader, n)?;
+        let Some(ascii) = reader.read_frame_as_ascii(config)? else {
+            break;
+        };
+
+        if writer.is_none() {
+            let (width, height) = get_size_from_ascii(&ascii, config.height_sample_scale, config.font_size);
+            writer = Some(FFmpegVideoWriter::with_size(config, width, height, config.output_fps(fps))?);
+        }
+
+        // Inputs that aren't live are read faster than real time, so wait until the frame is due
+        sleep(Duration::from_secs_f64((i - range.start) as f64 / fps).saturating_sub(start.elapsed()));
+        writer.as_mut().unwrap().write_ascii_frame(config, &ascii)?;
+        reporter.frame_written(n as u64);
+    }
+
+    if let Some(mut writer) = writer {
+        writer.close()?;
+    }
+    info!("Finished streaming ascii video to {output_path}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_live_output_formats() {
+        assert_eq!(live_output_format("rtmp://example.com/live/key"), Some("flv"));
+        assert_eq!(live_output_format("RTMPS://example.com/live/key"), Some("flv"));
+        assert_eq!(live_output_format("srt://example.com:9000"), Some("mpegts"));
+        assert_eq!(live_output_format("udp://239.0.0.1:1234"), Some("mpegts"));
+        assert_eq!(live_output_format("live/stream.m3u8"), Some(HLS_FORMAT));
+        assert_eq!(live_output_format("LIVE.M3U8"), Some(HLS_FORMAT));
+    }
+
+    #[test]
+    fn rejects_non_live_outputs() {
+        for path in ["output.mp4", "output.gif", "m3u8", "output.m3u8.mp4", "-", "", "rtsp://example.com/stream"] {
+            assert_eq!(live_output_format(path), None, "{path}");
+            assert!(!is_live_output(path), "{path}");
+        }
+        // Video files over http(s) are read, not pushed to, so only playlists are live outputs
+        assert_eq!(live_output_format("https://example.com/video.mp4"), None);
+    }
+
+    // Live output URLs are also readable as inputs, the config field they're given in decides the direction
+    #[test]
+    fn pushed_live_outputs_are_stream_urls() {
+        for url in ["rtmp://example.com/live/key", "rtmps://example.com/live/key", "srt://host:9000", "udp://host:1234"]
+        {
+            assert!(is_live_output(url) && is_stream_url(url), "{url}");
+        }
+        assert!(!is_stream_url("live/stream.m3u8"));
+    }
+}
@@ -1,12 +1,37 @@
+mod avio;
+mod cast;
 mod encoder;
 mod errors;
 mod ffmpeg;
+mod frames;
+mod interrupt;
+mod job;
+mod live;
+#[cfg(feature = "opencv")]
 mod opencv;
+mod range;
 mod reader;
+mod sequence;
+#[cfg(feature = "terminal")]
+mod terminal;
+mod text;
 mod video;
 mod writer;
 
+pub use cast::CastWriter;
 pub use ffmpeg::{
-    FFmpegVideoWriter, convert_ffmpeg_video_to_ascii, encode_ascii_frame_ffmpeg, read_video_frames_ffmpeg,
+    FFmpegVideoReader, FFmpegVideoWriter, convert_ffmpeg_video_to_ascii, encode_ascii_frame_ffmpeg,
+    read_video_frames_ffmpeg,
 };
+pub use frames::{AsciiFrames, VideoAsciiFrame};
+pub use job::{CancellationToken, JobProgress, VideoJob};
+pub use live::{is_live_output, live_output_format};
+#[cfg(feature = "opencv")]
+pub use opencv::OpenCVVideoReader;
+pub use range::TimePosition;
+pub use reader::Reader;
+pub use sequence::{ImageSequenceReader, image_sequence_frame_path, is_image_sequence};
+#[cfg(feature = "terminal")]
+pub use terminal::TerminalRenderer;
+pub use text::{TextVideo, TextVideoWriter, is_text_video, read_text_video, render_text_video};
 pub use video::*;
@@ -1,7 +1,6 @@
+use crate::ascii::{AsciiFrame, AsciiOptions, ascii_from_pixels};
 use crate::image::generate_ascii_image;
-use crate::util::constants::{
-    DARK_BGR_SCALAR, GREYSCALE_RAMP, REVERSE_GREYSCALE_RAMP, RGB_TO_GREYSCALE, WHITE_BGR_SCALAR,
-};
+use crate::util::constants::{DARK_BGR_SCALAR, WHITE_BGR_SCALAR};
 use crate::util::{UnsafeMat, get_size_from_ascii};
 use crate::video::encoder::Encoder;
 use crate::video::errors::Error;
@@ -12,26 +11,54 @@ use opencv::core::{CV_8UC3, Mat, MatTraitConst, MatTraitManual, Size, Vec3b};
 use opencv::hub_prelude::{VideoCaptureTraitConst, VideoWriterTrait};
 use opencv::videoio;
 use opencv::videoio::{VideoCaptureTrait, VideoWriter};
-use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use tracing::error;
 
 pub struct OpenCVVideoReader {
     pub capture: videoio::VideoCapture,
     total_frames: u64,
     fps: f64,
+    frame_size: (u32, u32),
     frames: Vec<UnsafeMat>,
 }
 impl OpenCVVideoReader {
     pub fn new(video_path: &str) -> VideoResult<Self> {
         let capture = videoio::VideoCapture::from_file(video_path, videoio::CAP_ANY)
             .map_err(|e| Error::VideoReadError(format!("Could not open video file at {video_path}: {e}")))?;
-        let total_frames = capture
+        let frame_count = capture
             .get(videoio::CAP_PROP_FRAME_COUNT)
-            .map_err(|e| Error::VideoReadError(format!("Could not get number of frames: {e}")))?
-            as u64;
+            .map_err(|e| Error::VideoReadError(format!("Could not get number of frames: {e}")))?;
+        // Network streams report no frame count, so are read until they end
+        let total_frames = if frame_count > 0.0 { frame_count as u64 } else { u64::MAX };
         let fps =
             capture.get(videoio::CAP_PROP_FPS).map_err(|e| Error::VideoReadError(format!("Could not get fps: {e}")))?;
+        let frame_size = (
+            capture.get(videoio::CAP_PROP_FRAME_WIDTH).unwrap_or(0.0) as u32,
+            capture.get(videoio::CAP_PROP_FRAME_HEIGHT).unwrap_or(0.0) as u32,
+        );
 
-        Ok(Self { capture, total_frames, fps, frames: Vec::with_capacity(total_frames as usize) })
+        let frames = if total_frames == u64::MAX { vec![] } else { Vec::with_capacity(total_frames as usize) };
+        Ok(Self { capture, total_frames, fps, frame_size, frames })
+    }
+
+    /// Opens a live capture device, as an index or a device path such as `/dev/video0`
+    pub fn from_device(device: &str) -> VideoResult<Self> {
+        let capture = match device.parse::<i32>() {
+            Ok(index) => videoio::VideoCapture::new(index, videoio::CAP_ANY),
+            Err(_) => videoio::VideoCapture::from_file(device, videoio::CAP_ANY),
+        }
+        .map_err(|e| Error::VideoReadError(format!("Could not open capture device {device}: {e}")))?;
+        if !capture.is_opened().unwrap_or(false) {
+            return Err(Error::VideoReadError(format!("Could not open capture device {device}")));
+        }
+
+        let fps =
+            capture.get(videoio::CAP_PROP_FPS).map_err(|e| Error::VideoReadError(format!("Could not get fps: {e}")))?;
+        let frame_size = (
+            capture.get(videoio::CAP_PROP_FRAME_WIDTH).unwrap_or(0.0) as u32,
+            capture.get(videoio::CAP_PROP_FRAME_HEIGHT).unwrap_or(0.0) as u32,
+        );
+
+        Ok(Self { capture, total_frames: u64::MAX, fps, frame_size, frames: vec![] })
     }
 
     fn read_single_frame(&mut self, config: &VideoConfig) -> VideoResult<UnsafeMat> {
@@ -39,6 +66,10 @@ impl OpenCVVideoReader {
 
         // CV_8UC3
         if !self.capture.read(&mut frame.0).expect("Could not read frame of video") {
+            // Streams of unknown length end when no more frames can be read, leaving the frame empty
+            if self.total_frames == u64::MAX {
+                return Ok(frame);
+            }
             return Err(Error::VideoReadError("Could not read frame from video".to_string()));
         }
 
@@ -60,19 +91,59 @@ impl Reader for OpenCVVideoReader {
         self.fps
     }
 
-    fn read_frame(&mut self, config: &VideoConfig) -> VideoResult<()> {
+    fn frame_size(&self) -> (u32, u32) {
+        self.frame_size
+    }
+
+    fn seek(&mut self, frame_index: u64) -> VideoResult<()> {
+        // Seeking by timestamp lands on the right frame for more backends than seeking by frame number, which
+        // some backends only estimate
+        let (property, position) = if self.fps > 0.0 {
+            (videoio::CAP_PROP_POS_MSEC, frame_index as f64 * 1000.0 / self.fps)
+        } else {
+            (videoio::CAP_PROP_POS_FRAMES, frame_index as f64)
+        };
+        self.capture
+            .set(property, position)
+            .map_err(|e| Error::VideoReadError(format!("Could not seek to frame {frame_index}: {e}")))?;
+        Ok(())
+    }
+
+    fn skip_frame(&mut self) -> VideoResult<()> {
+        // Grabbing without retrieving skips decoding the frame
+        self.capture.grab().map_err(|e| Error::VideoReadError(format!("Could not skip frame: {e}")))?;
+        Ok(())
+    }
+
+    fn read_frame(&mut self, config: &VideoConfig) -> VideoResult<bool> {
         let frame = self.read_single_frame(config)?;
+        if frame.empty() {
+            return Ok(false);
+        }
         self.frames.push(frame);
 
-        Ok(())
+        Ok(true)
     }
 
-    fn read_frame_as_ascii(&mut self, config: &VideoConfig) -> VideoResult<Vec<Vec<&str>>> {
+    fn read_frame_as_ascii(&mut self, config: &VideoConfig) -> VideoResult<Option<AsciiFrame>> {
         let frame = self.read_single_frame(config)?;
-        Ok(convert_opencv_video_to_ascii(&frame, &config))
+        Ok((!frame.empty()).then(|| convert_opencv_video_to_ascii(&frame, &config.into())))
+    }
+
+    fn read_ascii_frame(&mut self, options: &AsciiOptions) -> VideoResult<Option<AsciiFrame>> {
+        let mut frame = UnsafeMat(Mat::default());
+        let read =
+            self.capture.read(&mut frame.0).map_err(|e| Error::VideoReadError(format!("Could not read frame: {e}")))?;
+        if !read || frame.empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(convert_opencv_video_to_ascii(&frame, options)))
     }
 
     fn finish(&mut self) -> VideoResult<()> {
+        // Streams don't report a frame count, so it's known once every frame is read
+        self.total_frames = self.frames.len() as u64;
         Ok(())
     }
 }
@@ -90,7 +161,7 @@ impl OpenCVVideoWriter {
         let mut frames = vec![Mat::default(); reader.total_frames as usize];
 
         let input_frames = reader.frames;
-        let ascii = convert_opencv_video_to_ascii(&input_frames[0], &config);
+        let ascii = convert_opencv_video_to_ascii(&input_frames[0], &config.into());
         let (width, height) = get_size_from_ascii(&ascii, config.height_sample_scale, config.font_size);
         // Openh264 codec seems to have this dimension limitation so we cap it
         if width * height > 9437184 {
@@ -101,7 +172,8 @@ impl OpenCVVideoWriter {
         frames[0] = encode_ascii_frame_opencv(&config, &ascii, width, height);
 
         //println!("frame size: {:?}", output_frame_size);
-        let video_fps = if config.use_max_fps_for_output_video { config.max_fps as f64 } else { reader.fps };
+        let video_fps =
+            if config.use_max_fps_for_output_video { config.max_fps as f64 } else { config.output_fps(reader.fps) };
         // TODO: allow any video output
         let video_writer: VideoWriter = VideoWriter::new(
             config.output_video_path.as_ref().unwrap().as_str(),
@@ -118,7 +190,7 @@ impl OpenCVVideoWriter {
 
 impl Encoder for OpenCVVideoWriter {
     fn encode_frame(&mut self, config: &VideoConfig, frame_index: usize) -> VideoResult<()> {
-        let ascii = convert_opencv_video_to_ascii(&self.input_frames[frame_index], &config);
+        let ascii = convert_opencv_video_to_ascii(&self.input_frames[frame_index], &config.into());
         let frame = encode_ascii_frame_opencv(&config, &ascii, self.width, self.height);
         unsafe {
             let ptr = self.frames.as_ptr() as *mut Mat;
@@ -146,53 +218,27 @@ impl Writer for OpenCVVideoWriter {
 impl Drop for OpenCVVideoWriter {
     fn drop(&mut self) {
         if let Err(e) = self.close() {
-            eprintln!("OpenCVVideoWriter close error: {e}");
+            error!("OpenCVVideoWriter close error: {e}");
         }
     }
 }
 
 unsafe impl Sync for OpenCVVideoWriter {}
 
-/// Converts an opencv frame Matrix into ascii representation 2-d Vector
+/// Converts an opencv frame Matrix into ascii
 ///
 /// References https://github.com/luketio/asciiframe/blob/main/src/converter.rs#L15.
 #[inline]
-pub fn convert_opencv_video_to_ascii(frame: &UnsafeMat, config: &VideoConfig) -> Vec<Vec<&'static str>> {
-    let scale_down = config.scale_down;
-    let height_sample_scale = config.height_sample_scale;
-
-    let width = frame.cols();
-    let height = frame.rows();
-    //println!("width: {}, height: {}", width, height);
-    // TODO: scaled dims
-    let scaled_width = (width as f32 / scale_down) as usize;
-    let scaled_height = ((height as f32 / scale_down) / height_sample_scale) as usize;
-    //println!("scaled scaled_width: {}, scaled_height: {}", scaled_width, scaled_height);
-    let mut res = vec![vec![" "; scaled_width]; scaled_height];
-
-    // Invert greyscale, for dark backgrounds
-    let greyscale_ramp: &[&str] = if config.invert { &REVERSE_GREYSCALE_RAMP } else { &GREYSCALE_RAMP };
-
-    // SAFETY: operates pixels independently
-    res.par_iter_mut().enumerate().for_each(|(y, row)| {
-        // TODO: is this parallelizable?
-        // TODO: This is a bad sampling method when scaling down
-        (0..scaled_width).for_each(|x| {
-            let pix: &Vec3b = frame
-                .at_2d::<Vec3b>((y as f32 * scale_down * height_sample_scale) as i32, (x as f32 * scale_down) as i32)
-                .unwrap();
-            let greyscale_value = RGB_TO_GREYSCALE.0 * pix[0] as f32
-                + RGB_TO_GREYSCALE.1 * pix[1] as f32
-                + RGB_TO_GREYSCALE.2 * pix[2] as f32;
-            let index = (greyscale_value * (greyscale_ramp.len() - 1) as f32 / 255.0).ceil() as usize;
-            row[x] = greyscale_ramp[index];
-        })
-    });
-
-    res
+pub fn convert_opencv_video_to_ascii(frame: &UnsafeMat, options: &AsciiOptions) -> AsciiFrame {
+    // SAFETY: only reads pixels
+    ascii_from_pixels(frame.cols() as u32, frame.rows() as u32, options, |x, y| {
+        let pix: &Vec3b = frame.at_2d::<Vec3b>(y as i32, x as i32).unwrap();
+        // opencv uses BGR
+        [pix[2], pix[1], pix[0], u8::MAX]
+    })
 }
 
-pub fn encode_ascii_frame_opencv(config: &VideoConfig, ascii: &[Vec<&str>], width: u32, height: u32) -> Mat {
+pub fn encode_ascii_frame_opencv(config: &VideoConfig, ascii: &AsciiFrame, width: u32, height: u32) -> Mat {
     let frame = generate_ascii_image(ascii, width, height, config.invert, config.font_size);
     //println!("image frame width: {}, height: {}", frame.width(), frame.height());
 
@@ -0,0 +1,262 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::video::errors::Error;
+use crate::video::reader::Reader;
+use crate::video::{VideoConfig, VideoResult};
+
+/// Suffix of positions given as a frame number rather than a timestamp, e.g. `150f`
+const FRAME_SUFFIX: char = 'f';
+
+/// Position in a video, given as a timestamp such as `90`, `1:30`, `00:01:30.5` or `90.5s`, or as a frame
+/// number such as `150f`
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TimePosition {
+    Seconds(f64),
+    Frame(u64),
+}
+
+impl TimePosition {
+    /// Index of the frame at this position, in a video at `fps`
+    pub fn to_frame(self, fps: f64) -> u64 {
+        match self {
+            TimePosition::Seconds(seconds) => (seconds * fps).round() as u64,
+            TimePosition::Frame(frame) => frame,
+        }
+    }
+}
+
+impl FromStr for TimePosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid =
+            || format!("Invalid time position {s}, expected a timestamp like 1:30.5 or 90s, or a frame like 150f");
+
+        if let Some(frame) = s.strip_suffix(FRAME_SUFFIX) {
+            return frame.parse().map(TimePosition::Frame).map_err(|_| invalid());
+        }
+
+        // Timestamps are [[hh:]mm:]ss[.fff], with an optional `s` suffix for plain seconds
+        let s = s.strip_suffix('s').unwrap_or(s);
+        let parts: Vec<&str> = s.split(':').collect();
+        if parts.len() > 3 {
+            return Err(invalid());
+        }
+        let mut seconds = 0.0;
+        for (i, part) in parts.iter().enumerate() {
+            let value: f64 = part.parse().map_err(|_| invalid())?;
+            // Only the seconds may have a fraction, and minutes and seconds after the first part are below 60
+            let last = i == parts.len() - 1;
+            if !value.is_finite() || value < 0.0 || (!last && value.fract() != 0.0) || (i > 0 && value >= 60.0) {
+                return Err(invalid());
+            }
+            seconds = seconds * 60.0 + value;
+        }
+        Ok(TimePosition::Seconds(seconds))
+    }
+}
+
+impl TryFrom<String> for TimePosition {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for TimePosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimePosition::Seconds(seconds) => write!(f, "{seconds}s"),
+            TimePosition::Frame(frame) => write!(f, "{frame}{FRAME_SUFFIX}"),
+        }
+    }
+}
+
+/// Frames of a video selected by the `start`, `end`, `duration` and `every_nth_frame` settings
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FrameRange {
+    /// Index of the first selected frame
+    pub start: u64,
+    /// Index of the frame to stop before, or `None` to read until the end of the video
+    pub end: Option<u64>,
+    /// Number of frames between each selected frame
+    pub step: u64,
+}
+
+impl FrameRange {
+    /// Selects frames from a video at `fps` with `total_frames` frames, which is `u64::MAX` if unknown
+    pub(crate) fn new(config: &VideoConfig, fps: f64, total_frames: u64) -> VideoResult<Self> {
+        let start = config.start.map_or(0, |start| start.to_frame(fps));
+        let end = match (config.end, config.duration) {
+            (Some(end), _) => Some(end.to_frame(fps)),
+            (None, Some(duration)) => Some(start.saturating_add(duration.to_frame(fps))),
+            (None, None) => None,
+        };
+
+        if end.is_some_and(|end| end <= start) {
+            return Err(Error::VideoReadError(format!(
+                "Time range is empty, the end must be after the start at frame {start}"
+            )));
+        }
+        if total_frames != u64::MAX && start >= total_frames {
+            return Err(Error::VideoReadError(format!(
+                "Start at frame {start} is past the end of the video, which has {total_frames} frames"
+            )));
+        }
+
+        // An end past the end of the video is the same as reading to the end
+        let end = end.filter(|end| *end < total_frames);
+        Ok(Self { start, end, step: config.every_nth_frame.max(1) })
+    }
+
+    /// Number of selected frames, which is `u64::MAX` if the video's length is unknown and no end is set.
+    /// Video lengths may be estimated, so fewer frames may be read.
+    pub(crate) fn len(&self, total_frames: u64) -> u64 {
+        let end = self.end.unwrap_or(total_frames);
+        if end == u64::MAX { u64::MAX } else { (end - self.start).div_ceil(self.step) }
+    }
+
+    /// Source index of each selected frame.  Without an end this doesn't stop, and reading stops at the end
+    /// of the video instead.
+    pub(crate) fn frame_indices(&self) -> impl Iterator<Item = u64> + use<> {
+        (self.start..self.end.unwrap_or(u64::MAX)).step_by(self.step as usize)
+    }
+
+    /// Moves `reader` to the `n`th selected frame, which is about to be read.  The first frame is seeked to,
+    /// and frames between selected frames are skipped without being converted.
+    pub(crate) fn advance<R: Reader>(&self, reader: &mut R, n: usize) -> VideoResult<()> {
+        if n == 0 {
+            if self.start > 0 {
+                reader.seek(self.start)?;
+            }
+        } else {
+            for _ in 1..self.step {
+                reader.skip_frame()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video::VideoConfigBuilder;
+
+    fn parse(s: &str) -> Result<TimePosition, String> {
+        s.parse()
+    }
+
+    fn range(start: Option<&str>, end: Option<&str>, duration: Option<&str>, every_nth_frame: u64) -> FrameRange {
+        try_range(start, end, duration, every_nth_frame, 1000).unwrap()
+    }
+
+    fn try_range(
+        start: Option<&str>,
+        end: Option<&str>,
+        duration: Option<&str>,
+        every_nth_frame: u64,
+        total_frames: u64,
+    ) -> VideoResult<FrameRange> {
+        let config = VideoConfigBuilder::default()
+            .start(start.map(|s| parse(s).unwrap()))
+            .end(end.map(|s| parse(s).unwrap()))
+            .duration(duration.map(|s| parse(s).unwrap()))
+            .every_nth_frame(every_nth_frame)
+            .build()
+            .unwrap();
+        FrameRange::new(&config, 30.0, total_frames)
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(parse("90"), Ok(TimePosition::Seconds(90.0)));
+        assert_eq!(parse("90.5s"), Ok(TimePosition::Seconds(90.5)));
+        assert_eq!(parse("1:30"), Ok(TimePosition::Seconds(90.0)));
+        assert_eq!(parse("00:01:30.5"), Ok(TimePosition::Seconds(90.5)));
+        assert_eq!(parse(" 2:00:00 "), Ok(TimePosition::Seconds(7200.0)));
+        assert_eq!(parse("0"), Ok(TimePosition::Seconds(0.0)));
+    }
+
+    #[test]
+    fn parses_frames() {
+        assert_eq!(parse("150f"), Ok(TimePosition::Frame(150)));
+        assert_eq!(parse("0f"), Ok(TimePosition::Frame(0)));
+    }
+
+    #[test]
+    fn rejects_invalid_positions() {
+        for s in
+            ["", "f", "s", "abc", "-5", "1.5f", "-1f", "1:60", "1:00:60", "1.5:30", "1:2:3:4", "inf", "NaN", "1::30"]
+        {
+            assert!(parse(s).is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn displays_parseable_positions() {
+        for position in [TimePosition::Seconds(90.5), TimePosition::Frame(150)] {
+            assert_eq!(parse(&position.to_string()), Ok(position));
+        }
+    }
+
+    #[test]
+    fn converts_to_frames() {
+        assert_eq!(TimePosition::Seconds(1.5).to_frame(30.0), 45);
+        assert_eq!(TimePosition::Frame(45).to_frame(60.0), 45);
+    }
+
+    #[test]
+    fn selects_whole_video_by_default() {
+        let range = range(None, None, None, 1);
+        assert_eq!((range.start, range.end, range.step), (0, None, 1));
+        assert_eq!(range.len(1000), 1000);
+        assert_eq!(range.len(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn selects_start_and_end() {
+        let range = range(Some("1"), Some("60f"), None, 1);
+        assert_eq!((range.start, range.end), (30, Some(60)));
+        assert_eq!(range.len(1000), 30);
+        assert_eq!(range.frame_indices().collect::<Vec<_>>(), (30..60).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn selects_duration_from_start() {
+        let range = range(Some("10f"), None, Some("1"), 1);
+        assert_eq!((range.start, range.end), (10, Some(40)));
+        // The end takes precedence over the duration
+        assert_eq!(self::range(None, Some("20f"), Some("1"), 1).end, Some(20));
+    }
+
+    #[test]
+    fn selects_every_nth_frame() {
+        let range = range(Some("1f"), Some("11f"), None, 4);
+        assert_eq!(range.frame_indices().collect::<Vec<_>>(), vec![1, 5, 9]);
+        assert_eq!(range.len(1000), 3);
+        // 0 is treated as every frame
+        assert_eq!(self::range(None, None, None, 0).step, 1);
+    }
+
+    #[test]
+    fn end_past_video_reads_to_end() {
+        assert_eq!(range(None, Some("2000f"), None, 1).end, None);
+        assert_eq!(try_range(None, Some("2000f"), None, 1, u64::MAX).unwrap().end, Some(2000));
+    }
+
+    #[test]
+    fn rejects_empty_ranges() {
+        assert!(try_range(Some("60f"), Some("60f"), None, 1, 1000).is_err());
+        assert!(try_range(Some("2"), Some("1"), None, 1, 1000).is_err());
+        assert!(try_range(None, None, Some("0"), 1, 1000).is_err());
+        assert!(try_range(Some("1000f"), None, None, 1, 1000).is_err());
+        assert!(try_range(Some("1000f"), None, None, 1, u64::MAX).is_ok());
+    }
+}
@@ -1,12 +1,31 @@
+use crate::ascii::{AsciiFrame, AsciiOptions};
 use crate::video::{VideoConfig, VideoResult};
 
+/// Frame rate assumed for sources that don't report one
+pub(crate) const FALLBACK_FPS: f64 = 25.0;
+
 pub trait Reader {
+    /// Number of frames in the video, or `u64::MAX` for live sources, and streams whose length isn't known,
+    /// which are read until they end
     fn total_frames(&self) -> u64;
 
     fn fps(&self) -> f64;
 
-    fn read_frame(&mut self, config: &VideoConfig) -> VideoResult<()>;
-    fn read_frame_as_ascii(&mut self, config: &VideoConfig) -> VideoResult<Vec<Vec<&str>>>;
+    /// Width and height of the source frames, before any rotation
+    fn frame_size(&self) -> (u32, u32);
+
+    /// Seeks so the next frame read is the frame at `frame_index`
+    fn seek(&mut self, frame_index: u64) -> VideoResult<()>;
+    /// Skips the next frame, decoding it if needed, but without converting it
+    fn skip_frame(&mut self) -> VideoResult<()>;
+
+    /// Reads the next frame, keeping it to be encoded by the writer.  Returns false at the end of the video.
+    fn read_frame(&mut self, config: &VideoConfig) -> VideoResult<bool>;
+    /// Reads the next frame as ascii, keeping the source color of each cell if `config.export_colors` is set.
+    /// Returns `None` at the end of the video, as frames scaled down to no columns are still frames.
+    fn read_frame_as_ascii(&mut self, config: &VideoConfig) -> VideoResult<Option<AsciiFrame>>;
+    /// Reads the next frame as an [AsciiFrame], without rotating it.  Returns `None` at the end of the video.
+    fn read_ascii_frame(&mut self, options: &AsciiOptions) -> VideoResult<Option<AsciiFrame>>;
 
     fn finish(&mut self) -> VideoResult<()>;
 }
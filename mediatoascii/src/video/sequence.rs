@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::ImageFormat;
+
+use crate::ascii::{AsciiFrame, AsciiOptions};
+use crate::util::FFmpegFrame;
+use crate::video::errors::Error;
+use crate::video::ffmpeg::convert_ffmpeg_video_to_ascii;
+use crate::video::reader::Reader;
+use crate::video::{VideoConfig, VideoResult};
+
+/// ffmpeg's image2 demuxer looks for the first frame of a pattern within this range of start numbers
+const MAX_START_NUMBER: u64 = 4;
+
+/// A printf-style frame number pattern such as `frame_%04d.png`, split around the `%d`
+struct SequencePattern<'a> {
+    prefix: &'a str,
+    width: usize,
+    suffix: &'a str,
+}
+
+impl<'a> SequencePattern<'a> {
+    /// Parses the first `%d` or zero-padded `%0Nd` in `path`
+    fn parse(path: &'a str) -> Option<Self> {
+        let start = path.find('%')?;
+        let spec = &path[start + 1..];
+        let digits = spec.find(|c: char| !c.is_ascii_digit())?;
+        if !spec[digits..].starts_with('d') {
+            return None;
+        }
+        let width = if digits == 0 { 0 } else { spec[..digits].parse().ok()? };
+        Some(Self { prefix: &path[..start], width, suffix: &spec[digits + 1..] })
+    }
+
+    fn path(&self, index: u64) -> PathBuf {
+        PathBuf::from(format!("{}{:0width$}{}", self.prefix, index, self.suffix, width = self.width))
+    }
+}
+
+/// Whether `path` is an image sequence input: a directory of images, or a printf-style pattern such as
+/// `frame_%04d.png` matching existing images.  The first image must be decodable, so directories and
+/// patterns of other files aren't mistaken for image sequences.
+pub fn is_image_sequence(path: &str) -> bool {
+    list_image_sequence(path)
+        .ok()
+        .and_then(|files| files.into_iter().next())
+        .is_some_and(|first| image::image_dimensions(first).is_ok())
+}
+
+/// Path of the frame at `index` of an image sequence output, which is either a directory or printf-style
+/// pattern
+pub fn image_sequence_frame_path(output: &str, index: u64) -> PathBuf {
+    match SequencePattern::parse(output) {
+        Some(pattern) => pattern.path(index),
+        None => Path::new(output).join(format!("frame_{index:06}.png")),
+    }
+}
+
+/// Lists the frames of an image sequence input, in order
+fn list_image_sequence(path: &str) -> VideoResult<Vec<PathBuf>> {
+    if let Some(pattern) = SequencePattern::parse(path) {
+        // Like ffmpeg, frames are numbered consecutively from the first existing start number
+        let Some(start) = (0..=MAX_START_NUMBER).find(|i| pattern.path(*i).is_file()) else {
+            return Ok(vec![]);
+        };
+        return Ok((start..).map(|i| pattern.path(i)).take_while(|file| file.is_file()).collect());
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| Error::VideoReadError(format!("Could not read image sequence directory {path}: {e}")))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| file.is_file() && ImageFormat::from_path(file).is_ok())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Reads a sequence of image files as frames of a video, at a fixed frame rate
+pub struct ImageSequenceReader {
+    paths: Vec<PathBuf>,
+    fps: f64,
+    frame_size: (u32, u32),
+    next_frame: usize,
+    pub(crate) frames: Vec<FFmpegFrame>,
+}
+
+impl ImageSequenceReader {
+    pub fn new(path: &str, fps: f64) -> VideoResult<Self> {
+        let paths = list_image_sequence(path)?;
+        if paths.is_empty() {
+            return Err(Error::VideoReadError(format!("No images found for image sequence {path}")));
+        }
+        let frame_size = image::image_dimensions(&paths[0])
+            .map_err(|e| Error::VideoReadError(format!("Could not read image {}: {e}", paths[0].display())))?;
+
+        Ok(Self { frames: Vec::with_capacity(paths.len()), paths, fps, frame_size, next_frame: 0 })
+    }
+
+    fn read_single_frame(&mut self) -> VideoResult<FFmpegFrame> {
+        let Some(path) = self.paths.get(self.next_frame) else {
+            // End of the sequence, matches ffmpeg reader's behavior at EOF
+            return Ok(FFmpegFrame::default());
+        };
+        self.next_frame += 1;
+
+        let image = image::open(path)
+            .map_err(|e| Error::VideoReadError(format!("Could not open image {}: {e}", path.display())))?;
+        Ok(FFmpegFrame::from_rgb_image(&image.into_rgb8()))
+    }
+}
+
+impl Reader for ImageSequenceReader {
+    fn total_frames(&self) -> u64 {
+        self.paths.len() as u64
+    }
+
+    fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    fn frame_size(&self) -> (u32, u32) {
+        self.frame_size
+    }
+
+    fn seek(&mut self, frame_index: u64) -> VideoResult<()> {
+        self.next_frame = frame_index as usize;
+        Ok(())
+    }
+
+    fn skip_frame(&mut self) -> VideoResult<()> {
+        self.next_frame += 1;
+        Ok(())
+    }
+
+    fn read_frame(&mut self, _config: &VideoConfig) -> VideoResult<bool> {
+        let frame = self.read_single_frame()?;
+        if frame.is_empty() {
+            return Ok(false);
+        }
+        self.frames.push(frame);
+        Ok(true)
+    }
+
+    fn read_frame_as_ascii(&mut self, config: &VideoConfig) -> VideoResult<Option<AsciiFrame>> {
+        let frame = self.read_single_frame()?;
+        Ok((!frame.is_empty()).then(|| convert_ffmpeg_video_to_ascii(&frame, config)))
+    }
+
+    fn read_ascii_frame(&mut self, options: &AsciiOptions) -> VideoResult<Option<AsciiFrame>> {
+        let frame = self.read_single_frame()?;
+        Ok((!frame.is_empty()).then(|| frame.to_ascii(options)))
+    }
+
+    fn finish(&mut self) -> VideoResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbImage;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn parse(path: &str) -> Option<(&str, usize, &str)> {
+        SequencePattern::parse(path).map(|pattern| (pattern.prefix, pattern.width, pattern.suffix))
+    }
+
+    #[test]
+    fn parses_patterns() {
+        assert_eq!(parse("frame_%d.png"), Some(("frame_", 0, ".png")));
+        assert_eq!(parse("frames/frame_%04d.png"), Some(("frames/frame_", 4, ".png")));
+        assert_eq!(parse("%d"), Some(("", 0, "")));
+        assert_eq!(parse("%010d.jpg"), Some(("", 10, ".jpg")));
+        // Only the first pattern is a frame number
+        assert_eq!(parse("%d_%d.png"), Some(("", 0, "_%d.png")));
+    }
+
+    #[test]
+    fn rejects_non_patterns() {
+        for path in ["frame.png", "frames/", "100%.png", "frame_%", "frame_%04", "frame_%s.png", "frame_%-4d.png"] {
+            assert_eq!(parse(path), None, "{path}");
+        }
+        // Widths too large to parse
+        assert_eq!(parse("frame_%99999999999999999999999d.png"), None);
+    }
+
+    #[test]
+    fn formats_frame_paths() {
+        let pattern = SequencePattern::parse("frame_%04d.png").unwrap();
+        assert_eq!(pattern.path(0), PathBuf::from("frame_0000.png"));
+        assert_eq!(pattern.path(42), PathBuf::from("frame_0042.png"));
+        // Numbers wider than the padding aren't truncated
+        assert_eq!(pattern.path(123456), PathBuf::from("frame_123456.png"));
+        assert_eq!(SequencePattern::parse("%d.png").unwrap().path(7), PathBuf::from("7.png"));
+    }
+
+    #[test]
+    fn output_frame_paths() {
+        assert_eq!(image_sequence_frame_path("out/frame_%03d.png", 5), PathBuf::from("out/frame_005.png"));
+        assert_eq!(image_sequence_frame_path("out", 5), Path::new("out").join("frame_000005.png"));
+    }
+
+    #[test]
+    fn detects_only_sequences_of_existing_images() {
+        let dir = TempDir::new().unwrap();
+        let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+        assert!(!is_image_sequence(&path("")));
+        assert!(!is_image_sequence(&path("frame_%04d.png")));
+
+        // Files with an image extension that can't be decoded
+        fs::write(path("frame_0000.png"), "not a png").unwrap();
+        fs::write(path("notes.txt"), "not an image").unwrap();
+        assert!(!is_image_sequence(&path("")));
+        assert!(!is_image_sequence(&path("frame_%04d.png")));
+
+        RgbImage::new(2, 2).save(path("frame_0000.png")).unwrap();
+        assert!(is_image_sequence(&path("")));
+        assert!(is_image_sequence(&path("frame_%04d.png")));
+        assert!(!is_image_sequence(&path("other_%04d.png")));
+        assert!(!is_image_sequence(&path("notes.txt")));
+        assert!(!is_image_sequence(&path("missing")));
+    }
+}
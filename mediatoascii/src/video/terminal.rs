@@ -0,0 +1,682 @@
+use std::io::{self, Stdout, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{
+    self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use crossterm::{execute, queue};
+use signal_hook::SigId;
+use signal_hook::consts::SIGINT;
+#[cfg(unix)]
+use signal_hook::consts::SIGWINCH;
+
+use crate::ascii::AsciiFrame;
+use crate::util::fit_scale_down;
+use crate::video::errors::Error;
+use crate::video::range::FrameRange;
+use crate::video::reader::{FALLBACK_FPS, Reader};
+use crate::video::{VideoConfig, VideoResult};
+
+/// Seconds seeked by the left and right arrow keys
+const SEEK_SECONDS: f64 = 5.0;
+/// Seconds seeked by the up and down arrow keys
+const LONG_SEEK_SECONDS: f64 = 30.0;
+const SPEED_STEP: f64 = 0.25;
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 4.0;
+/// How often keyboard input is checked while paused or waiting for the next frame
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Late frames are skipped one by one, unless playback fell further behind than this, when seeking is faster
+const MAX_SKIPPED_SECONDS: f64 = 2.0;
+const CONTROLS_HELP: &str = "space: pause, arrows: seek, +/-: speed, i: invert, l: loop, q: quit";
+const LIVE_CONTROLS_HELP: &str = "space: pause, i: invert, q: quit";
+
+/// Draws ascii frames to the terminal without flickering.
+///
+/// Frames are drawn to the alternate screen with the cursor hidden, and only the lines that changed
+/// since the previous frame are rewritten.  The terminal is restored when the renderer is dropped,
+/// including when playback is interrupted with Ctrl-C.
+///
+/// Raw mode is enabled, if supported, so keys can be read without waiting for enter.
+pub struct TerminalRenderer {
+    stdout: Stdout,
+    previous_lines: Vec<String>,
+    raw_mode: bool,
+    /// Columns and rows of the terminal
+    size: (u16, u16),
+    interrupted: Arc<AtomicBool>,
+    /// Set on SIGWINCH.  Other platforms have no resize signal, so poll the size instead.
+    #[cfg(unix)]
+    resized: Arc<AtomicBool>,
+    sigint: SigId,
+    #[cfg(unix)]
+    sigwinch: SigId,
+}
+
+impl TerminalRenderer {
+    pub fn new() -> VideoResult<Self> {
+        // Catch Ctrl-C so playback can stop and restore the terminal, rather than being killed mid-frame
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let sigint = signal_hook::flag::register(SIGINT, Arc::clone(&interrupted)).map_err(terminal_error)?;
+        #[cfg(unix)]
+        let resized = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        let sigwinch = signal_hook::flag::register(SIGWINCH, Arc::clone(&resized)).map_err(terminal_error)?;
+
+        // Queries the window size with the TIOCGWINSZ ioctl on unix
+        let size = terminal::size().map_err(terminal_error)?;
+
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, Hide, Clear(ClearType::All)).map_err(terminal_error)?;
+        // Fails when stdin isn't a terminal, in which case playback just isn't interactive
+        let raw_mode = enable_raw_mode().is_ok();
+
+        Ok(Self {
+            stdout,
+            previous_lines: vec![],
+            raw_mode,
+            size,
+            interrupted,
+            #[cfg(unix)]
+            resized,
+            sigint,
+            #[cfg(unix)]
+            sigwinch,
+        })
+    }
+
+    /// Whether Ctrl-C was pressed since the renderer was created
+    pub fn interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+    }
+
+    /// Whether keyboard input can be read, i.e. raw mode is enabled
+    pub fn interactive(&self) -> bool {
+        self.raw_mode
+    }
+
+    /// Columns and rows of the terminal, as of the last call to [TerminalRenderer::check_resized]
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    /// Whether the terminal was resized since the last call.  If so, the screen is cleared and the next
+    /// frame is drawn in full.
+    pub fn check_resized(&mut self) -> VideoResult<bool> {
+        #[cfg(unix)]
+        if !self.resized.swap(false, Ordering::Relaxed) {
+            return Ok(false);
+        }
+
+        let size = terminal::size().map_err(terminal_error)?;
+        if size == self.size {
+            return Ok(false);
+        }
+
+        self.size = size;
+        self.previous_lines.clear();
+        execute!(self.stdout, Clear(ClearType::All)).map_err(terminal_error)?;
+        Ok(true)
+    }
+
+    /// Draws a frame, rewriting only the lines that differ from the previously drawn frame
+    pub fn draw(&mut self, ascii: &AsciiFrame) -> VideoResult<()> {
+        let lines = ascii.row_strings();
+
+        // Queue the whole frame and write it at once, so it never shows half drawn
+        let mut buffer: Vec<u8> = vec![];
+        for (row, line) in lines.iter().enumerate() {
+            if self.previous_lines.get(row) != Some(line) {
+                queue!(buffer, MoveTo(0, row as u16), Print(line), Clear(ClearType::UntilNewLine))
+                    .map_err(terminal_error)?;
+            }
+        }
+        if lines.len() < self.previous_lines.len() {
+            queue!(buffer, MoveTo(0, lines.len() as u16), Clear(ClearType::FromCursorDown)).map_err(terminal_error)?;
+        }
+
+        let mut handle = self.stdout.lock();
+        handle.write_all(&buffer).and_then(|_| handle.flush()).map_err(terminal_error)?;
+
+        self.previous_lines = lines;
+        Ok(())
+    }
+
+    /// Draws a status line below the frame, truncated to the terminal width
+    pub fn draw_status(&mut self, status: &str) -> VideoResult<()> {
+        let status: String = status.chars().take(self.size.0 as usize).collect();
+        execute!(
+            self.stdout,
+            MoveTo(0, self.previous_lines.len() as u16),
+            Print(status),
+            Clear(ClearType::UntilNewLine)
+        )
+        .map_err(terminal_error)
+    }
+}
+
+impl Drop for TerminalRenderer {
+    fn drop(&mut self) {
+        if self.raw_mode {
+            let _ = disable_raw_mode();
+        }
+        let _ = execute!(self.stdout, Show, LeaveAlternateScreen);
+        signal_hook::low_level::unregister(self.sigint);
+        #[cfg(unix)]
+        signal_hook::low_level::unregister(self.sigwinch);
+    }
+}
+
+fn terminal_error(e: io::Error) -> Error {
+    Error::VideoWriteError(format!("terminal error: {e}"))
+}
+
+/// Fits frames from `reader` to the terminal by overriding `config.scale_down`, leaving `reserved_rows`
+/// below the frame
+fn fit_to_terminal<R: Reader>(reader: &R, config: &mut VideoConfig, (columns, rows): (u16, u16), reserved_rows: u16) {
+    let (mut width, mut height) = reader.frame_size();
+    // Rotating by 90 degrees either way swaps the dimensions
+    if config.should_rotate && config.rotate != 1 {
+        (width, height) = (height, width);
+    }
+    config.scale_down =
+        fit_scale_down(width, height, columns, rows.saturating_sub(reserved_rows), config.height_sample_scale);
+}
+
+/// Maps wall time to media time at the playback speed, so frames are shown when their presentation
+/// timestamps are due rather than accumulating drift from per-frame sleeps
+struct PlaybackClock {
+    anchor: Instant,
+    anchor_media_time: f64,
+    speed: f64,
+}
+
+impl PlaybackClock {
+    fn new(media_time: f64, speed: f64) -> Self {
+        Self { anchor: Instant::now(), anchor_media_time: media_time, speed }
+    }
+
+    /// Current media time in seconds
+    fn media_time(&self) -> f64 {
+        self.anchor_media_time + self.anchor.elapsed().as_secs_f64() * self.speed
+    }
+
+    /// Wall time until `media_time` is due
+    fn until(&self, media_time: f64) -> Duration {
+        Duration::from_secs_f64(((media_time - self.media_time()) / self.speed).max(0.0))
+    }
+}
+
+/// Playback state controlled by the keyboard
+struct Playback {
+    /// Index of the next frame to read
+    position: u64,
+    /// First frame of the time range being played, which seeking and looping stay within
+    start: u64,
+    /// Frame to stop before, at the end of the time range or of the video
+    total_frames: u64,
+    fps: f64,
+    /// Live sources, like capture devices, and streams of unknown length have no end and can't be seeked
+    live: bool,
+    clock: PlaybackClock,
+    paused: bool,
+    looping: bool,
+    quit: bool,
+    /// Read and draw the next frame even while paused, to show the result of seeking or toggling invert
+    step: bool,
+}
+
+impl Playback {
+    fn handle_key<R: Reader>(&mut self, key: KeyEvent, reader: &mut R, config: &mut VideoConfig) -> VideoResult<()> {
+        // Windows also reports key releases
+        if key.kind == KeyEventKind::Release {
+            return Ok(());
+        }
+
+        let frames = |seconds: f64| (seconds * self.fps) as i64;
+        let speed = self.clock.speed;
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char(' ') => {
+                self.paused = !self.paused;
+                self.restart_clock(speed);
+            }
+            KeyCode::Left => self.seek(reader, -frames(SEEK_SECONDS))?,
+            KeyCode::Right => self.seek(reader, frames(SEEK_SECONDS))?,
+            KeyCode::Down => self.seek(reader, -frames(LONG_SEEK_SECONDS))?,
+            KeyCode::Up => self.seek(reader, frames(LONG_SEEK_SECONDS))?,
+            KeyCode::Char('+') | KeyCode::Char('=') if !self.live => {
+                self.restart_clock((speed + SPEED_STEP).min(MAX_SPEED))
+            }
+            KeyCode::Char('-') | KeyCode::Char('_') if !self.live => {
+                self.restart_clock((speed - SPEED_STEP).max(MIN_SPEED))
+            }
+            KeyCode::Char('i') => {
+                config.invert = !config.invert;
+                self.redraw_current_frame(reader)?;
+            }
+            KeyCode::Char('l') if !self.live => self.looping = !self.looping,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Restarts the clock from the next frame, e.g. after seeking or resuming
+    fn restart_clock(&mut self, speed: f64) {
+        self.clock = PlaybackClock::new(self.position as f64 / self.fps, speed);
+    }
+
+    /// Seeks `offset` frames from the current frame
+    fn seek<R: Reader>(&mut self, reader: &mut R, offset: i64) -> VideoResult<()> {
+        if self.live {
+            return Ok(());
+        }
+        let current = self.position.saturating_sub(1) as i64;
+        let target = (current + offset).clamp(self.start as i64, self.total_frames.saturating_sub(1) as i64) as u64;
+        reader.seek(target)?;
+        self.position = target;
+        self.restart_clock(self.clock.speed);
+        self.step = true;
+        Ok(())
+    }
+
+    /// Index of the latest frame whose timestamp is due
+    fn due_frame(&self) -> u64 {
+        (self.clock.media_time() * self.fps) as u64
+    }
+
+    /// Drops the frames from the current frame up to `due`, which are already late, without converting them
+    fn drop_late_frames<R: Reader>(&mut self, reader: &mut R, due: u64) -> VideoResult<()> {
+        let late_frames = due.saturating_sub(self.position);
+        if late_frames as f64 > self.fps * MAX_SKIPPED_SECONDS {
+            reader.seek(due)?;
+        } else {
+            for _ in 0..late_frames {
+                reader.skip_frame()?;
+            }
+        }
+        self.position = self.position.max(due);
+        Ok(())
+    }
+
+    /// Rewinds by a frame so the current frame is read and drawn again
+    fn redraw_current_frame<R: Reader>(&mut self, reader: &mut R) -> VideoResult<()> {
+        // Live sources can't go back, so the next frame is drawn instead
+        if !self.live && self.position > self.start {
+            self.position -= 1;
+            reader.seek(self.position)?;
+        }
+        self.step = true;
+        Ok(())
+    }
+
+    fn status(&self) -> String {
+        let state = if self.paused { "paused " } else { "playing" };
+        let elapsed = format_timestamp(self.position.saturating_sub(1) as f64 / self.fps);
+        if self.live {
+            return format!("{state} {elapsed} / live  {:.2} fps  [{LIVE_CONTROLS_HELP}]", self.fps);
+        }
+        let looping = if self.looping { "  loop" } else { "" };
+        format!(
+            "{state} {elapsed} / {}  {:.2} fps  {:.2}x{looping}  [{CONTROLS_HELP}]",
+            format_timestamp(self.total_frames as f64 / self.fps),
+            self.fps,
+            self.clock.speed
+        )
+    }
+}
+
+/// Formats `seconds` as `mm:ss.s`
+fn format_timestamp(seconds: f64) -> String {
+    // Rounded to tenths first, so 59.96 seconds carries over to the next minute
+    let tenths = (seconds.max(0.0) * 10.0).round() as u64;
+    format!("{:02}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+/// Plays the video from `reader` as ascii in the terminal, until it ends or playback is quit.
+///
+/// Frames are scheduled against a clock: frames that are late are dropped without being converted, and
+/// the current frame stays on screen until the next is due.  At most `config.max_fps` frames are drawn
+/// per second, so `config.every_nth_frame` is ignored.  Playback covers the time range from `config.start`
+/// to `config.end` or `config.duration`, which seeking and looping stay within.
+///
+/// If the terminal supports raw mode, playback is controlled with the keyboard: space to pause, left/right
+/// arrows to seek 5 seconds and up/down arrows to seek 30 seconds, +/- to change speed, `i` to toggle
+/// invert, `l` to toggle looping, and `q` or Ctrl-C to quit.
+///
+/// Live sources and streams of unknown length, whose `total_frames` is `u64::MAX`, are played without
+/// dropping frames, and can't be seeked or have their speed changed.
+pub(crate) fn play_in_terminal<R: Reader>(reader: &mut R, config: &mut VideoConfig) -> VideoResult<()> {
+    let fps = if reader.fps() > 0.0 { reader.fps() } else { FALLBACK_FPS };
+    let min_draw_interval =
+        if config.max_fps > 0 { Duration::from_secs_f64(1.0 / config.max_fps as f64) } else { Duration::ZERO };
+
+    let mut renderer = TerminalRenderer::new()?;
+    let status_rows = if renderer.interactive() { 1 } else { 0 };
+    if config.fit_terminal {
+        fit_to_terminal(reader, config, renderer.size(), status_rows);
+    }
+
+    let total_frames = reader.total_frames();
+    let range = FrameRange::new(config, fps, total_frames)?;
+    let live = total_frames == u64::MAX;
+    // Live sources can't be seeked, so their start is skipped to by reading
+    if live {
+        for _ in 0..range.start {
+            reader.skip_frame()?;
+        }
+    } else {
+        range.advance(reader, 0)?;
+    }
+    let mut playback = Playback {
+        position: range.start,
+        start: range.start,
+        total_frames: range.end.unwrap_or(total_frames),
+        fps,
+        live,
+        clock: PlaybackClock::new(range.start as f64 / fps, 1.0),
+        paused: false,
+        looping: false,
+        quit: false,
+        step: false,
+    };
+    let mut last_draw: Option<Instant> = None;
+    loop {
+        if renderer.check_resized()? {
+            if config.fit_terminal {
+                fit_to_terminal(reader, config, renderer.size(), status_rows);
+            }
+            playback.redraw_current_frame(reader)?;
+        }
+        if renderer.interactive() {
+            while event::poll(Duration::ZERO).map_err(terminal_error)? {
+                if let Event::Key(key) = event::read().map_err(terminal_error)? {
+                    playback.handle_key(key, reader, config)?;
+                }
+            }
+        }
+        if renderer.interrupted() || playback.quit {
+            break;
+        }
+
+        if playback.live {
+            if playback.position >= playback.total_frames {
+                break;
+            }
+            let frame_time = playback.position as f64 / fps;
+            let lag = playback.clock.media_time() - frame_time;
+            if lag < 0.0 {
+                // Capture devices produce frames in real time, but streams of unknown length may be read faster,
+                // so are paced by the clock
+                sleep(playback.clock.until(frame_time).min(INPUT_POLL_INTERVAL));
+                continue;
+            }
+            if lag > MAX_SKIPPED_SECONDS {
+                // Too far behind to catch up, e.g. after the source stalled, so the clock restarts from here
+                playback.restart_clock(playback.clock.speed);
+            }
+
+            if playback.paused && !playback.step {
+                // Keep consuming frames while paused, so playback resumes from the latest frame
+                reader.skip_frame()?;
+                playback.position += 1;
+                renderer.draw_status(&playback.status())?;
+                continue;
+            }
+            playback.step = false;
+
+            let ascii = reader.read_frame_as_ascii(config)?;
+            playback.position += 1;
+            let Some(ascii) = ascii else {
+                // The stream ended, or the device was disconnected
+                break;
+            };
+            // Frames arriving faster than max_fps are dropped
+            if playback.paused || last_draw.is_none_or(|last_draw| last_draw.elapsed() >= min_draw_interval) {
+                last_draw = Some(Instant::now());
+                renderer.draw(&ascii)?;
+                if renderer.interactive() {
+                    renderer.draw_status(&playback.status())?;
+                }
+            }
+            continue;
+        }
+
+        if playback.paused {
+            if playback.step && playback.position < playback.total_frames {
+                let ascii = reader.read_frame_as_ascii(config)?;
+                playback.position += 1;
+                if let Some(ascii) = ascii {
+                    renderer.draw(&ascii)?;
+                }
+            }
+            playback.step = false;
+            renderer.draw_status(&playback.status())?;
+            sleep(INPUT_POLL_INTERVAL);
+            continue;
+        }
+        playback.step = false;
+
+        let due = playback.due_frame();
+        if due >= playback.total_frames {
+            if !playback.looping {
+                break;
+            }
+            reader.seek(playback.start)?;
+            playback.position = playback.start;
+            playback.restart_clock(playback.clock.speed);
+            continue;
+        }
+        if playback.position > due {
+            // Ahead of the clock, so keep showing the current frame until the next is due.  The wait is capped
+            // so keyboard input stays responsive.
+            sleep(playback.clock.until(playback.position as f64 / fps).min(INPUT_POLL_INTERVAL));
+            continue;
+        }
+
+        // Behind the clock, so drop the frames that are already late
+        playback.drop_late_frames(reader, due)?;
+
+        let draw_start = Instant::now();
+        let ascii = reader.read_frame_as_ascii(config)?;
+        playback.position += 1;
+        let Some(ascii) = ascii else {
+            // The source had fewer frames than it reported
+            playback.total_frames = due;
+            continue;
+        };
+        renderer.draw(&ascii)?;
+        if renderer.interactive() {
+            renderer.draw_status(&playback.status())?;
+        }
+
+        // Frames that become due while waiting are dropped, limiting the draw rate to max_fps
+        sleep(min_draw_interval.saturating_sub(draw_start.elapsed()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ascii::AsciiOptions;
+
+    /// Tolerance of wall clock assertions, for slow test machines
+    const TOLERANCE: f64 = 0.05;
+
+    /// Records how frames were read, seeked and skipped
+    #[derive(Default)]
+    struct FakeReader {
+        seeks: Vec<u64>,
+        skipped: u64,
+    }
+
+    impl Reader for FakeReader {
+        fn total_frames(&self) -> u64 {
+            1000
+        }
+
+        fn fps(&self) -> f64 {
+            25.0
+        }
+
+        fn frame_size(&self) -> (u32, u32) {
+            (4, 4)
+        }
+
+        fn seek(&mut self, frame_index: u64) -> VideoResult<()> {
+            self.seeks.push(frame_index);
+            Ok(())
+        }
+
+        fn skip_frame(&mut self) -> VideoResult<()> {
+            self.skipped += 1;
+            Ok(())
+        }
+
+        fn read_frame(&mut self, _config: &VideoConfig) -> VideoResult<bool> {
+            Ok(true)
+        }
+
+        fn read_frame_as_ascii(&mut self, _config: &VideoConfig) -> VideoResult<Option<AsciiFrame>> {
+            Ok(Some(AsciiFrame::new(1, 1)))
+        }
+
+        fn read_ascii_frame(&mut self, _options: &AsciiOptions) -> VideoResult<Option<AsciiFrame>> {
+            Ok(Some(AsciiFrame::new(1, 1)))
+        }
+
+        fn finish(&mut self) -> VideoResult<()> {
+            Ok(())
+        }
+    }
+
+    /// Clock that started `elapsed` seconds ago at media time 0
+    fn clock_started(elapsed: f64, speed: f64) -> PlaybackClock {
+        PlaybackClock { anchor: Instant::now() - Duration::from_secs_f64(elapsed), anchor_media_time: 0.0, speed }
+    }
+
+    fn new_playback(fps: f64, position: u64, live: bool) -> Playback {
+        Playback {
+            position,
+            start: 10,
+            total_frames: 1000,
+            fps,
+            live,
+            clock: PlaybackClock::new(position as f64 / fps, 1.0),
+            paused: false,
+            looping: false,
+            quit: false,
+            step: false,
+        }
+    }
+
+    fn assert_secs(actual: Duration, expected: f64) {
+        let actual = actual.as_secs_f64();
+        assert!((actual - expected).abs() < TOLERANCE, "{actual} is not {expected}");
+    }
+
+    #[test]
+    fn clock_deadlines_at_fractional_fps() {
+        // Frame 24 of NTSC film is due just after a second
+        let clock = clock_started(0.0, 1.0);
+        assert_secs(clock.until(24.0 / 23.976), 1.001);
+        // Frames of sources slower than a frame per second are due seconds apart
+        assert_secs(clock.until(1.0 / 0.5), 2.0);
+        assert_secs(clock.until(3.0 / 0.5), 6.0);
+        // Wall time is scaled by the speed
+        assert_secs(clock_started(0.0, 2.0).until(3.0 / 0.5), 3.0);
+        // Frames that are already due are shown straight away
+        assert_eq!(clock_started(1.0, 1.0).until(0.5), Duration::ZERO);
+
+        let mut playback = new_playback(23.976, 0, false);
+        playback.clock = clock_started(10.0, 1.0);
+        assert_eq!(playback.due_frame(), 239);
+        playback.fps = 0.5;
+        assert_eq!(playback.due_frame(), 5);
+    }
+
+    #[test]
+    fn drops_late_frames() {
+        let mut reader = FakeReader::default();
+        let mut playback = new_playback(25.0, 100, false);
+        playback.drop_late_frames(&mut reader, 110).unwrap();
+        assert_eq!((playback.position, reader.skipped, reader.seeks.len()), (110, 10, 0));
+
+        // Frames that aren't late aren't dropped
+        playback.drop_late_frames(&mut reader, 110).unwrap();
+        assert_eq!((playback.position, reader.skipped), (110, 10));
+
+        // Seeks instead of skipping more than MAX_SKIPPED_SECONDS of frames
+        playback.drop_late_frames(&mut reader, 161).unwrap();
+        assert_eq!((playback.position, reader.skipped, reader.seeks.clone()), (161, 10, vec![161]));
+    }
+
+    #[test]
+    fn seeks_within_the_time_range() {
+        let mut reader = FakeReader::default();
+        let mut config = VideoConfig::default();
+        let mut playback = new_playback(25.0, 101, false);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        // Seeks are relative to the frame on screen, the one before the next frame to read
+        playback.handle_key(key(KeyCode::Right), &mut reader, &mut config).unwrap();
+        assert_eq!(playback.position, 225);
+        assert!((playback.clock.anchor_media_time - 9.0).abs() < f64::EPSILON);
+        assert!(playback.step);
+        playback.handle_key(key(KeyCode::Down), &mut reader, &mut config).unwrap();
+        assert_eq!(playback.position, 10);
+        playback.handle_key(key(KeyCode::Up), &mut reader, &mut config).unwrap();
+        playback.handle_key(key(KeyCode::Up), &mut reader, &mut config).unwrap();
+        assert_eq!(playback.position, 999);
+        assert_eq!(reader.seeks, [225, 10, 759, 999]);
+
+        // Live sources can't be seeked
+        let mut live = new_playback(25.0, 101, true);
+        live.handle_key(key(KeyCode::Left), &mut reader, &mut config).unwrap();
+        assert_eq!(live.position, 101);
+        assert_eq!(reader.seeks.len(), 4);
+    }
+
+    #[test]
+    fn pausing_restarts_the_clock_from_the_next_frame() {
+        let mut reader = FakeReader::default();
+        let mut config = VideoConfig::default();
+        let mut playback = new_playback(25.0, 0, false);
+        playback.clock = clock_started(30.0, 1.5);
+        playback.position = 50;
+        let space = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE);
+
+        playback.handle_key(space, &mut reader, &mut config).unwrap();
+        assert!(playback.paused);
+        assert_eq!(playback.clock.anchor_media_time, 2.0);
+        assert_eq!(playback.clock.speed, 1.5);
+
+        // Resuming continues from the same frame, however long playback was paused
+        playback.clock = clock_started(30.0, 1.5);
+        playback.handle_key(space, &mut reader, &mut config).unwrap();
+        assert!(!playback.paused);
+        assert_eq!(playback.due_frame(), 50);
+
+        playback.handle_key(KeyEvent::new(KeyCode::Char('+'), KeyModifiers::NONE), &mut reader, &mut config).unwrap();
+        assert_eq!(playback.clock.speed, 1.75);
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(0.0), "00:00.0");
+        assert_eq!(format_timestamp(5.04), "00:05.0");
+        assert_eq!(format_timestamp(61.3), "01:01.3");
+        assert_eq!(format_timestamp(59.96), "01:00.0");
+        assert_eq!(format_timestamp(3599.9), "59:59.9");
+        assert_eq!(format_timestamp(5400.0), "90:00.0");
+    }
+}
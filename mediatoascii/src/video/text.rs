@@ -0,0 +1,330 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, DynamicImage, Frame};
+use tracing::info;
+
+use crate::ascii::AsciiFrame;
+use crate::image::generate_ascii_image;
+use crate::util::stdio::{create_output, is_stdio};
+use crate::util::get_size_from_dimensions;
+use crate::video::errors::Error;
+use crate::video::writer::Writer;
+use crate::video::{FFmpegVideoWriter, VideoConfig, VideoResult};
+
+/// First line of a text video archive, or of the metadata file in a text video directory
+const TEXT_VIDEO_MAGIC: &str = "MEDIATOASCII-TEXT-VIDEO 1";
+/// Holds the header of a text video directory, alongside the frame files
+const TEXT_VIDEO_METADATA_FILE: &str = "video.txt";
+/// Prefix of the line that starts each frame in a single file text video archive
+const FRAME_DELIMITER: &str = "%% frame ";
+/// Frame lines starting with this are archived with an extra `%`, which is removed when they are read, so
+/// frame text can't be mistaken for a [FRAME_DELIMITER]
+const ESCAPED_PREFIX: &str = "%%";
+/// Prefix of the header line holding the frame rate
+const FPS_PREFIX: &str = "fps ";
+
+/// Ascii frames of a text video, each frame being rows of text separated by newlines
+pub struct TextVideo {
+    pub fps: f64,
+    pub frames: Vec<String>,
+}
+
+enum TextVideoOutput {
+    /// Single file archive, or stdout
+    File(BufWriter<Box<dyn Write + Send>>),
+    Directory(PathBuf),
+}
+
+/// Writes ascii frames to a text video, either as a single archive file with a header and frame
+/// delimiters, or as a directory with one `frame_NNNNNN.txt` file per frame.  Archives can also be
+/// written to stdout, with a path of `-`.
+///
+/// Text videos can be edited with regular text tools, and rendered back into a video by passing them
+/// as the input to [crate::video::process_video].
+pub struct TextVideoWriter {
+    output: TextVideoOutput,
+}
+
+impl TextVideoWriter {
+    pub fn new(path: &str, fps: f64, as_directory: bool) -> VideoResult<Self> {
+        let header = format!("{TEXT_VIDEO_MAGIC}\n{FPS_PREFIX}{fps}\n");
+
+        let output = if as_directory {
+            let dir = PathBuf::from(path);
+            fs::create_dir_all(&dir)
+                .map_err(|e| Error::VideoWriteError(format!("Could not create text video directory {path}: {e}")))?;
+            fs::write(dir.join(TEXT_VIDEO_METADATA_FILE), header)
+                .map_err(|e| Error::VideoWriteError(format!("Could not write text video header: {e}")))?;
+            TextVideoOutput::Directory(dir)
+        } else {
+            let file = create_output(path)
+                .map_err(|e| Error::VideoWriteError(format!("Could not create text video at {path}: {e}")))?;
+            let mut writer = BufWriter::new(file);
+            writer
+                .write_all(header.as_bytes())
+                .map_err(|e| Error::VideoWriteError(format!("Could not write text video header: {e}")))?;
+            TextVideoOutput::File(writer)
+        };
+
+        Ok(Self { output })
+    }
+
+    pub fn write_frame(&mut self, frame_index: u64, ascii: &AsciiFrame) -> VideoResult<()> {
+        let text = ascii.to_string();
+        match &mut self.output {
+            TextVideoOutput::File(writer) => {
+                let mut archived = format!("{FRAME_DELIMITER}{frame_index}\n");
+                for line in text.lines() {
+                    if line.starts_with(ESCAPED_PREFIX) {
+                        archived.push('%');
+                    }
+                    archived.push_str(line);
+                    archived.push('\n');
+                }
+                writer.write_all(archived.as_bytes())
+            }
+            TextVideoOutput::Directory(dir) => fs::write(dir.join(format!("frame_{frame_index:06}.txt")), text),
+        }
+        .map_err(|e| Error::VideoWriteError(format!("Could not write text video frame {frame_index}: {e}")))
+    }
+
+    pub fn close(&mut self) -> VideoResult<()> {
+        if let TextVideoOutput::File(writer) = &mut self.output {
+            writer.flush().map_err(|e| Error::VideoWriteError(format!("Could not flush text video: {e}")))?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `path` is a text video archive or directory written by [TextVideoWriter]
+pub fn is_text_video(path: &str) -> bool {
+    let path = Path::new(path);
+    let header_path = if path.is_dir() { path.join(TEXT_VIDEO_METADATA_FILE) } else { path.to_path_buf() };
+
+    // Only read the magic, as this is also called on (potentially large) binary video files
+    let mut magic = [0u8; TEXT_VIDEO_MAGIC.len()];
+    File::open(header_path).and_then(|mut file| file.read_exact(&mut magic)).is_ok()
+        && magic == TEXT_VIDEO_MAGIC.as_bytes()
+}
+
+fn parse_header<'a>(mut lines: impl Iterator<Item = &'a str>, path: &str) -> VideoResult<f64> {
+    if lines.next().map(str::trim_end) != Some(TEXT_VIDEO_MAGIC) {
+        return Err(Error::VideoReadError(format!("{path} is not a text video")));
+    }
+
+    lines
+        .next()
+        .and_then(|line| line.trim_end().strip_prefix(FPS_PREFIX))
+        .and_then(|fps| fps.parse::<f64>().ok())
+        .filter(|fps| *fps > 0.0)
+        .ok_or_else(|| Error::VideoReadError(format!("Text video at {path} is missing a valid fps header")))
+}
+
+/// Reads a text video archive or directory written by [TextVideoWriter]
+pub fn read_text_video(path: &str) -> VideoResult<TextVideo> {
+    let read = |file: &Path| {
+        fs::read_to_string(file)
+            .map_err(|e| Error::VideoReadError(format!("Could not read text video file {}: {e}", file.display())))
+    };
+
+    let dir = Path::new(path);
+    if dir.is_dir() {
+        let fps = parse_header(read(&dir.join(TEXT_VIDEO_METADATA_FILE))?.lines(), path)?;
+
+        let mut frame_files: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(|e| Error::VideoReadError(format!("Could not read text video directory {path}: {e}")))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| {
+                file.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("frame_") && name.ends_with(".txt"))
+            })
+            .collect();
+        // Frame numbers are zero padded, so lexicographic order is frame order
+        frame_files.sort();
+
+        let frames = frame_files.iter().map(|file| read(file)).collect::<VideoResult<Vec<String>>>()?;
+        Ok(TextVideo { fps, frames })
+    } else {
+        let contents = read(dir)?;
+        let mut lines = contents.lines();
+        let fps = parse_header(&mut lines, path)?;
+
+        let mut frames: Vec<String> = vec![];
+        for line in lines {
+            if line.starts_with(FRAME_DELIMITER) {
+                frames.push(String::new());
+            } else if let Some(frame) = frames.last_mut() {
+                let line = line.strip_prefix('%').filter(|line| line.starts_with(ESCAPED_PREFIX)).unwrap_or(line);
+                frame.push_str(line);
+                frame.push('\n');
+            }
+        }
+        Ok(TextVideo { fps, frames })
+    }
+}
+
+/// Renders the text video at `config.video_path` to `config.output_video_path`, as a GIF if the output
+/// has a `.gif` extension, or is stdout with an `output_format` of `gif`, or an mp4 otherwise.
+pub fn render_text_video(config: &VideoConfig) -> VideoResult<()> {
+    let output_path = config
+        .output_video_path
+        .as_deref()
+        .ok_or_else(|| Error::VideoWriteError("An output video path is required to render a text video".to_string()))?;
+
+    let text_video = read_text_video(&config.video_path)?;
+    let frames: Vec<AsciiFrame> = text_video.frames.iter().map(|frame| AsciiFrame::from_text(frame)).collect();
+    if frames.is_empty() {
+        return Err(Error::VideoReadError(format!("Text video at {} has no frames", config.video_path)));
+    }
+
+    // Frames may have been edited to different sizes, so the output is sized to fit the largest
+    let columns = frames.iter().map(|frame| frame.width).max().unwrap_or(0);
+    let rows = frames.iter().map(|frame| frame.height).max().unwrap_or(0);
+    let (width, height) = get_size_from_dimensions(columns, rows, config.height_sample_scale, config.font_size);
+
+    info!("Rendering {} text video frames to {}", frames.len(), output_path);
+
+    let gif = if is_stdio(output_path) {
+        config.output_format.as_deref().is_some_and(|format| format.eq_ignore_ascii_case("gif"))
+    } else {
+        Path::new(output_path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gif"))
+    };
+    if gif {
+        let file = create_output(output_path)
+            .map_err(|e| Error::VideoWriteError(format!("Could not create gif at {output_path}: {e}")))?;
+        let mut encoder = GifEncoder::new(BufWriter::new(file));
+        encoder.set_repeat(Repeat::Infinite).map_err(|e| Error::VideoWriteError(format!("gif error: {e}")))?;
+
+        // Millisecond fractions so fractional frame rates like 29.97 keep their timing
+        let delay = Delay::from_numer_denom_ms(1_000_000, (text_video.fps * 1000.0) as u32);
+        for ascii in &frames {
+            let image = generate_ascii_image(ascii, width, height, config.invert, config.font_size);
+            encoder
+                .encode_frame(Frame::from_parts(DynamicImage::ImageRgb8(image).into_rgba8(), 0, 0, delay))
+                .map_err(|e| Error::VideoWriteError(format!("gif encode frame error: {e}")))?;
+        }
+    } else {
+        let mut writer = FFmpegVideoWriter::with_size(config, width, height, text_video.fps)?;
+        for (i, ascii) in frames.iter().enumerate() {
+            writer.encode_ascii_frame(config, ascii)?;
+            writer.write_frame(i)?;
+        }
+        writer.close()?;
+    }
+
+    info!("Finished rendering text video to {}", output_path);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::video::VideoConfigBuilder;
+
+    const FRAMES: [&str; 3] = ["#+-\n-+#\n", "   \n@@@\n", "é.ü\n . \n"];
+
+    fn temp_path(dir: &TempDir, name: &str) -> String {
+        dir.path().join(name).to_str().unwrap().to_string()
+    }
+
+    fn write(path: &str, fps: f64, as_directory: bool, frames: &[&str]) {
+        let mut writer = TextVideoWriter::new(path, fps, as_directory).unwrap();
+        for (i, frame) in frames.iter().enumerate() {
+            writer.write_frame(i as u64, &AsciiFrame::from_text(frame)).unwrap();
+        }
+        writer.close().unwrap();
+    }
+
+    fn assert_round_trip(as_directory: bool) {
+        let dir = TempDir::new().unwrap();
+        let path = temp_path(&dir, "video.txt");
+        write(&path, 7.5, as_directory, &FRAMES);
+
+        assert!(is_text_video(&path));
+        let video = read_text_video(&path).unwrap();
+        assert_eq!(video.fps, 7.5);
+        assert_eq!(video.frames, FRAMES);
+    }
+
+    #[test]
+    fn archive_round_trip() {
+        assert_round_trip(false);
+    }
+
+    #[test]
+    fn directory_round_trip() {
+        assert_round_trip(true);
+    }
+
+    #[test]
+    fn empty_video_round_trip() {
+        let dir = TempDir::new().unwrap();
+        for as_directory in [false, true] {
+            let path = temp_path(&dir, &format!("empty-{as_directory}"));
+            write(&path, 30.0, as_directory, &[]);
+            let video = read_text_video(&path).unwrap();
+            assert_eq!((video.fps, video.frames.len()), (30.0, 0));
+        }
+    }
+
+    #[test]
+    fn archive_round_trip_with_delimiter_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = temp_path(&dir, "delimiters.txt");
+        let frames = ["%% frame 7\n%%%%%%%%%%\n", "%%frame\n%.%.%.%\n", "%%% frame 1\n%% frame 1 \n"];
+        write(&path, 30.0, false, &frames);
+
+        let video = read_text_video(&path).unwrap();
+        assert_eq!(video.frames, frames);
+    }
+
+    #[test]
+    fn rendering_an_empty_video_fails() {
+        let dir = TempDir::new().unwrap();
+        let path = temp_path(&dir, "empty.txt");
+        write(&path, 30.0, false, &[]);
+
+        let config = VideoConfigBuilder::default()
+            .video_path(path)
+            .output_video_path(Some(temp_path(&dir, "empty.gif")))
+            .build()
+            .unwrap();
+        assert!(matches!(render_text_video(&config), Err(Error::VideoReadError(_))));
+        assert!(!dir.path().join("empty.gif").exists());
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let dir = TempDir::new().unwrap();
+        let path = temp_path(&dir, "invalid.txt");
+        for contents in [
+            "",
+            "not a text video\nfps 30\n",
+            "MEDIATOASCII-TEXT-VIDEO 1\n",
+            "MEDIATOASCII-TEXT-VIDEO 1\nfps 0\n",
+            "MEDIATOASCII-TEXT-VIDEO 1\nfps -30\n",
+            "MEDIATOASCII-TEXT-VIDEO 1\nfps thirty\n",
+        ] {
+            fs::write(&path, contents).unwrap();
+            assert!(matches!(read_text_video(&path), Err(Error::VideoReadError(_))), "{contents:?}");
+        }
+    }
+
+    #[test]
+    fn detects_only_text_videos() {
+        let dir = TempDir::new().unwrap();
+        let path = temp_path(&dir, "detect.txt");
+        assert!(!is_text_video(&path));
+        fs::write(&path, "MEDIATOASCII").unwrap();
+        assert!(!is_text_video(&path));
+        fs::write(&path, "MEDIATOASCII-TEXT-VIDEO 1\nfps 30\n").unwrap();
+        assert!(is_text_video(&path));
+    }
+}
@@ -1,45 +1,68 @@
-use std::io;
-use std::io::Write;
-use std::thread::sleep;
-use std::time::{Duration, SystemTime};
+use std::fs;
 
-use crate::util::ascii_to_str;
+use crate::ascii::{AsciiFrame, AsciiOptions, RenderOptions};
+use crate::image::generate_ascii_image;
+use crate::util::get_size_from_ascii;
+use crate::util::progress::Stage;
 use crate::util::constants::MAGIC_HEIGHT_TO_WIDTH_RATIO;
+use crate::util::export::{AsciiExport, AsciiExportFrame, ExportFormat};
 use crate::util::file_util::{check_file_exists, check_valid_file};
+use crate::util::stdio::{PeekedStdin, is_stdio};
+#[cfg(feature = "async")]
+use crate::util::task::Task;
+use crate::video::cast::CastWriter;
 use crate::video::encoder::Encoder;
 use crate::video::errors::Error;
 use crate::video::ffmpeg::FFmpegVideoReader;
+use crate::video::interrupt::StreamInterrupt;
+use crate::video::job::{JobReporter, VideoJob};
+use crate::video::live::{is_live_output, stream_live};
+#[cfg(feature = "opencv")]
 use crate::video::opencv::{OpenCVVideoReader, OpenCVVideoWriter};
+use crate::video::range::{FrameRange, TimePosition};
 use crate::video::reader::Reader;
+use crate::video::sequence::{ImageSequenceReader, image_sequence_frame_path, is_image_sequence};
+#[cfg(feature = "terminal")]
+use crate::video::terminal::play_in_terminal;
+use crate::video::text::{TextVideoWriter, is_text_video, render_text_video};
 use crate::video::writer::Writer;
 use crate::video::FFmpegVideoWriter;
 use derive_builder::Builder;
-use indicatif::ProgressBar;
 use serde::Deserialize;
-
-/// We track progress percentage in a global static mut as we only support 1 job at a time right now
-pub static mut PROGRESS_PERCENTAGE: f32 = 0.0;
-
-/// Current frame being processed during read step
-pub static mut READ_CURRENT_FRAME: u64 = 0;
-/// Current frame being processed during encode step
-pub static mut ENCODE_CURRENT_FRAME: u64 = 0;
-/// Current frame being processed during write step
-pub static mut WRITE_CURRENT_FRAME: u64 = 0;
-
-/// Total number of frames in the video
-pub static mut TOTAL_FRAMES: u64 = 0;
-
-/// Flag to signal cancellation of video processing
-pub static mut CANCEL_REQUESTED: bool = false;
+use tracing::{debug, error, info, info_span, trace};
 
 pub type VideoResult<T> = Result<T, crate::video::errors::Error>;
 
 #[derive(Builder, Debug, Deserialize)]
 #[builder(default)]
+#[serde(default)]
 pub struct VideoConfig {
-    /// Input Video file
+    /// Input Video file, or a text video written via `output_text_path` to render it to `output_video_path`.
+    /// Also accepts image sequences, as a directory of images or a printf-style pattern such as
+    /// `frame_%04d.png`.  A path of `-` reads the video from stdin.
     pub video_path: String,
+    /// ffmpeg demuxer name of a video read from stdin, such as `matroska` or `mpegts`, for containers that
+    /// can't be probed from their contents
+    pub input_format: Option<String>,
+    /// Bytes already read from stdin, such as by [crate::util::media::detect_stdin_media_type], which are
+    /// read before the rest of stdin when `video_path` is `-`
+    #[serde(skip)]
+    pub stdin: PeekedStdin,
+    /// Frame rate of image sequence inputs
+    pub sequence_fps: f64,
+    /// Live capture device to play in the terminal, or stream to a live `output_video_path`, instead of
+    /// `video_path`, as a device index or a device path such as `/dev/video0`
+    pub capture_device: Option<String>,
+    /// Position to start converting or playing the video from, as a timestamp or a frame number
+    pub start: Option<TimePosition>,
+    /// Position to stop converting or playing the video before, as a timestamp or a frame number.  Takes
+    /// precedence over `duration`.
+    pub end: Option<TimePosition>,
+    /// Length of the video to convert or play from `start`, as a timestamp or a number of frames
+    pub duration: Option<TimePosition>,
+    /// Only convert every nth frame of the video, skipping the frames between them without decoding them to
+    /// ascii.  Output videos play at the input's FPS divided by this, so they keep the input's duration.
+    pub every_nth_frame: u64,
     /// Multiplier to scale down input dimensions by when converting to ASCII.  For large frames,
     /// recommended to scale down more so output file size is more reasonable.  Affects output quality.
     /// Note: the output dimensions will also depend on the `font-size` setting.
@@ -69,24 +92,99 @@ pub struct VideoConfig {
     /// Supports most image formats, and .mp4 video outputs.
     /// Images will be resized to fit the ascii text.  Videos will honor the aspect ratio of the
     /// input, but resolution will be scaled differently approximately to `(height|width) / scale_down * font_size`.
+    /// A path of `-` streams the video to stdout, in the container set by `output_format`.
     pub output_video_path: Option<String>,
+    /// ffmpeg muxer name of a video streamed to stdout, such as `matroska`.  Defaults to `mp4`, which is
+    /// fragmented so it can be written to a pipe.
+    pub output_format: Option<String>,
+    /// Output path for an asciinema v2 `.cast` recording of the ascii frames, timed from the input
+    /// video's FPS.  Ignored if `output_video_path` is set.
+    pub output_cast_path: Option<String>,
+    /// Output path for a text video of the ascii frames, which can be edited with text tools and
+    /// rendered back to a video by passing it as `video_path`.  Ignored if `output_video_path` or
+    /// `output_cast_path` is set.
+    pub output_text_path: Option<String>,
+    /// Write the text video as a directory with one file per frame, instead of a single file
+    pub text_frames_as_directory: bool,
+    /// Output path for a structured (.json, or .msgpack with the `msgpack` feature) export of the ascii
+    /// frames and their timestamps.  Ignored if any of the other outputs are set.
+    pub output_export_path: Option<String>,
+    /// Format of the structured export.  Defaults to the format of the export path's extension, or JSON,
+    /// such as for exports written to stdout.
+    pub export_format: Option<ExportFormat>,
+    /// Include the source color of each cell in structured exports
+    pub export_colors: bool,
+    /// Include the source luminance of each cell in structured exports
+    pub export_luminance: bool,
+    /// Output directory, or printf-style pattern such as `ascii_%04d.png`, to write each rendered ascii
+    /// frame to as a numbered image.  Ignored if any of the other outputs are set.
+    pub output_frames_path: Option<String>,
     /// Overwrite any output file if it already exists
     pub overwrite: bool,
+    /// Fit terminal playback to the terminal size, overriding `scale_down` while preserving the aspect
+    /// ratio.  The video is re-fit whenever the terminal is resized.
+    pub fit_terminal: bool,
     /// Use the max_fps setting for video file outputs.
     pub use_max_fps_for_output_video: bool,
     /// Rotate the input (0 = 90 CLOCKWISE, 1 = 180, 2 = 90 COUNTER-CLOCKWISE)
     pub rotate: i32,
     #[serde(skip)]
     pub should_rotate: bool,
+    /// Read and write videos with OpenCV instead of ffmpeg.  Needs the `opencv` feature.
     pub use_opencv: bool,
     // /// Number of threads for parallel processing during encode step. [default: number of logical CPU cores]
     // pub num_threads: u8,
 }
 
+impl VideoConfig {
+    /// Whether any output is set, otherwise the video is played in the terminal
+    pub(crate) fn has_output(&self) -> bool {
+        self.output_video_path.is_some()
+            || self.output_cast_path.is_some()
+            || self.output_text_path.is_some()
+            || self.output_export_path.is_some()
+            || self.output_frames_path.is_some()
+    }
+
+    /// FPS of outputs converted from an input at `fps`, which only keep every nth frame
+    pub(crate) fn output_fps(&self, fps: f64) -> f64 {
+        fps / self.every_nth_frame.max(1) as f64
+    }
+}
+
+impl From<&VideoConfig> for AsciiOptions {
+    fn from(config: &VideoConfig) -> Self {
+        AsciiOptions {
+            scale_down: config.scale_down,
+            height_sample_scale: config.height_sample_scale,
+            invert: config.invert,
+            colors: config.export_colors,
+        }
+    }
+}
+
+impl From<&VideoConfig> for RenderOptions {
+    fn from(config: &VideoConfig) -> Self {
+        RenderOptions {
+            font_size: config.font_size,
+            height_sample_scale: config.height_sample_scale,
+            invert: config.invert,
+        }
+    }
+}
+
 impl Default for VideoConfig {
     fn default() -> Self {
         VideoConfig {
             video_path: "".to_string(),
+            input_format: None,
+            stdin: PeekedStdin::default(),
+            sequence_fps: 25.0,
+            capture_device: None,
+            start: None,
+            end: None,
+            duration: None,
+            every_nth_frame: 1,
             scale_down: 1.0,
             font_size: 12.0,
             height_sample_scale: MAGIC_HEIGHT_TO_WIDTH_RATIO,
@@ -94,7 +192,17 @@ impl Default for VideoConfig {
             max_fps: 10,
             bitrate: None,
             output_video_path: None,
+            output_format: None,
+            output_cast_path: None,
+            output_text_path: None,
+            text_frames_as_directory: false,
+            output_export_path: None,
+            export_format: None,
+            export_colors: false,
+            export_luminance: false,
+            output_frames_path: None,
             overwrite: false,
+            fit_terminal: false,
             use_max_fps_for_output_video: false,
             rotate: -1,
             should_rotate: false,
@@ -105,47 +213,96 @@ impl Default for VideoConfig {
 }
 
 pub enum VideoReader {
+    #[cfg(feature = "opencv")]
     OpenCV(OpenCVVideoReader),
     FFmpeg(FFmpegVideoReader),
+    ImageSequence(ImageSequenceReader),
 }
 impl Reader for VideoReader {
     fn total_frames(&self) -> u64 {
         match self {
+            #[cfg(feature = "opencv")]
             VideoReader::OpenCV(e) => e.total_frames(),
             VideoReader::FFmpeg(e) => e.total_frames(),
+            VideoReader::ImageSequence(e) => e.total_frames(),
         }
     }
 
     fn fps(&self) -> f64 {
         match self {
+            #[cfg(feature = "opencv")]
             VideoReader::OpenCV(e) => e.fps(),
             VideoReader::FFmpeg(e) => e.fps(),
+            VideoReader::ImageSequence(e) => e.fps(),
+        }
+    }
+
+    fn frame_size(&self) -> (u32, u32) {
+        match self {
+            #[cfg(feature = "opencv")]
+            VideoReader::OpenCV(e) => e.frame_size(),
+            VideoReader::FFmpeg(e) => e.frame_size(),
+            VideoReader::ImageSequence(e) => e.frame_size(),
+        }
+    }
+
+    fn seek(&mut self, frame_index: u64) -> VideoResult<()> {
+        match self {
+            #[cfg(feature = "opencv")]
+            VideoReader::OpenCV(e) => e.seek(frame_index),
+            VideoReader::FFmpeg(e) => e.seek(frame_index),
+            VideoReader::ImageSequence(e) => e.seek(frame_index),
+        }
+    }
+
+    fn skip_frame(&mut self) -> VideoResult<()> {
+        match self {
+            #[cfg(feature = "opencv")]
+            VideoReader::OpenCV(e) => e.skip_frame(),
+            VideoReader::FFmpeg(e) => e.skip_frame(),
+            VideoReader::ImageSequence(e) => e.skip_frame(),
         }
     }
 
-    fn read_frame(&mut self, config: &VideoConfig) -> VideoResult<()> {
+    fn read_frame(&mut self, config: &VideoConfig) -> VideoResult<bool> {
         match self {
+            #[cfg(feature = "opencv")]
             VideoReader::OpenCV(e) => e.read_frame(config),
             VideoReader::FFmpeg(e) => e.read_frame(config),
+            VideoReader::ImageSequence(e) => e.read_frame(config),
         }
     }
 
-    fn read_frame_as_ascii(&mut self, config: &VideoConfig) -> VideoResult<Vec<Vec<&str>>> {
+    fn read_frame_as_ascii(&mut self, config: &VideoConfig) -> VideoResult<Option<AsciiFrame>> {
         match self {
+            #[cfg(feature = "opencv")]
             VideoReader::OpenCV(e) => e.read_frame_as_ascii(config),
             VideoReader::FFmpeg(e) => e.read_frame_as_ascii(config),
+            VideoReader::ImageSequence(e) => e.read_frame_as_ascii(config),
+        }
+    }
+
+    fn read_ascii_frame(&mut self, options: &AsciiOptions) -> VideoResult<Option<AsciiFrame>> {
+        match self {
+            #[cfg(feature = "opencv")]
+            VideoReader::OpenCV(e) => e.read_ascii_frame(options),
+            VideoReader::FFmpeg(e) => e.read_ascii_frame(options),
+            VideoReader::ImageSequence(e) => e.read_ascii_frame(options),
         }
     }
 
     fn finish(&mut self) -> VideoResult<()> {
         match self {
+            #[cfg(feature = "opencv")]
             VideoReader::OpenCV(e) => e.finish(),
             VideoReader::FFmpeg(e) => e.finish(),
+            VideoReader::ImageSequence(e) => e.finish(),
         }
     }
 }
 
 pub enum VideoWriter {
+    #[cfg(feature = "opencv")]
     OpenCV(OpenCVVideoWriter),
     FFmpeg(FFmpegVideoWriter),
 }
@@ -154,14 +311,20 @@ impl TryFrom<(&VideoConfig, VideoReader)> for VideoWriter {
 
     fn try_from((config, reader): (&VideoConfig, VideoReader)) -> Result<Self, Self::Error> {
         match reader {
+            #[cfg(feature = "opencv")]
             VideoReader::OpenCV(e) => Ok(VideoWriter::OpenCV(OpenCVVideoWriter::new(&config, e)?)),
             VideoReader::FFmpeg(e) => Ok(VideoWriter::FFmpeg(FFmpegVideoWriter::new(&config, e)?)),
+            VideoReader::ImageSequence(e) => {
+                let fps = config.output_fps(e.fps());
+                Ok(VideoWriter::FFmpeg(FFmpegVideoWriter::from_frames(&config, e.frames, fps)?))
+            }
         }
     }
 }
 impl Encoder for VideoWriter {
     fn encode_frame(&mut self, config: &VideoConfig, frame_index: usize) -> VideoResult<()> {
         match self {
+            #[cfg(feature = "opencv")]
             VideoWriter::OpenCV(e) => e.encode_frame(config, frame_index),
             VideoWriter::FFmpeg(e) => e.encode_frame(config, frame_index),
         }
@@ -170,6 +333,7 @@ impl Encoder for VideoWriter {
 impl Writer for VideoWriter {
     fn write_frame(&mut self, frame_index: usize) -> VideoResult<()> {
         match self {
+            #[cfg(feature = "opencv")]
             VideoWriter::OpenCV(e) => e.write_frame(frame_index),
             VideoWriter::FFmpeg(e) => e.write_frame(frame_index),
         }
@@ -177,200 +341,409 @@ impl Writer for VideoWriter {
 
     fn close(&mut self) -> VideoResult<()> {
         match self {
+            #[cfg(feature = "opencv")]
             VideoWriter::OpenCV(e) => e.close(),
             VideoWriter::FFmpeg(e) => e.close(),
         }
     }
 }
 
+/// Opens the video at `path`, or the capture device at `path` if `capture_device`, with OpenCV
+#[cfg(feature = "opencv")]
+fn open_opencv_reader(path: &str, capture_device: bool) -> VideoResult<VideoReader> {
+    let reader = if capture_device { OpenCVVideoReader::from_device(path)? } else { OpenCVVideoReader::new(path)? };
+    Ok(VideoReader::OpenCV(reader))
+}
+
+#[cfg(not(feature = "opencv"))]
+fn open_opencv_reader(_path: &str, _capture_device: bool) -> VideoResult<VideoReader> {
+    Err(Error::FeatureDisabled("opencv"))
+}
+
+/// Videos can only be played in the terminal with the `terminal` feature, otherwise they need an output
+#[cfg(not(feature = "terminal"))]
+fn play_in_terminal(_reader: &mut VideoReader, _config: &mut VideoConfig) -> VideoResult<()> {
+    Err(Error::FeatureDisabled("terminal"))
+}
+
+/// Plays a live capture device in the terminal until playback is quit
+fn play_capture_device(device: &str, mut config: VideoConfig, reporter: &mut JobReporter) -> VideoResult<()> {
+    let live_output = config.output_video_path.as_deref().is_some_and(is_live_output);
+    if config.has_output() && !live_output {
+        return Err(Error::VideoReadError(
+            "Live capture can only be played in the terminal, or streamed to a live output".to_string(),
+        ));
+    }
+
+    let mut reader = if config.use_opencv {
+        open_opencv_reader(device, true)?
+    } else {
+        VideoReader::FFmpeg(FFmpegVideoReader::from_device(device)?)
+    };
+    config.should_rotate = config.rotate > -1 && config.rotate < 3;
+
+    reporter.probe_finished(reader.frame_size(), reader.fps(), reader.total_frames());
+    if live_output {
+        stream_live(&mut reader, &config, reporter)
+    } else {
+        reporter.stage(Stage::Playing, u64::MAX);
+        play_in_terminal(&mut reader, &mut config)
+    }
+}
+
+/// Interval of the frames dropped from output videos to honor `max_fps` with `use_max_fps_for_output_video`,
+/// or `None` if every frame is kept, such as for sources that are no faster than `max_fps`
+fn output_frame_cut(config: &VideoConfig, orig_fps: f64) -> Option<u64> {
+    let frame_cut = orig_fps as u64 / config.max_fps.max(1);
+    (config.use_max_fps_for_output_video && frame_cut > 1).then_some(frame_cut)
+}
+
 /// Processes video
 ///
 /// References https://github.com/luketio/asciiframe/blob/7f23d8843278ad9cd4b53ff7110005aceeec1fcb/src/renderer.rs#L69.
-pub fn process_video(mut config: VideoConfig) -> VideoResult<()> {
-    // Reset cancellation flag and progress tracking
-    unsafe {
-        CANCEL_REQUESTED = false;
-        READ_CURRENT_FRAME = 0;
-        ENCODE_CURRENT_FRAME = 0;
-        WRITE_CURRENT_FRAME = 0;
-        TOTAL_FRAMES = 0;
-        PROGRESS_PERCENTAGE = 0.0;
+pub fn process_video(config: VideoConfig) -> VideoResult<()> {
+    process_video_job(config, &VideoJob::new())
+}
+
+/// Processes video on tokio's blocking thread pool, resolving to the result when the returned [Task] is
+/// awaited.  Dropping the task cancels the conversion.  Must be called within a tokio runtime.
+#[cfg(feature = "async")]
+pub fn process_video_async(config: VideoConfig) -> Task<VideoResult<()>> {
+    process_video_job_async(config, VideoJob::new())
+}
+
+/// Processes video as `job` on tokio's blocking thread pool, see [process_video_async].  The task's progress
+/// stream takes the place of the job's observer, and the job can still be polled and cancelled through
+/// clones of it.
+#[cfg(feature = "async")]
+pub fn process_video_job_async(config: VideoConfig, job: VideoJob) -> Task<VideoResult<()>> {
+    let cancellation = job.cancellation_token().clone();
+    Task::spawn(Some(Box::new(move || cancellation.cancel())), move |progress| job.with_observer(progress).run(config))
+}
+
+/// Processes video as `job`, which tracks its progress and can cancel it from another thread
+pub fn process_video_job(config: VideoConfig, job: &VideoJob) -> VideoResult<()> {
+    let input = config.capture_device.as_deref().unwrap_or(&config.video_path).to_string();
+    let _span = info_span!("video_job", id = job.id(), %input).entered();
+
+    let mut reporter = JobReporter::new(job);
+    let result = run_video_job(config, job, &mut reporter);
+    if let Err(e) = result.as_ref() {
+        error!("Video job failed: {e}");
     }
+    reporter.finish(&result);
+    result
+}
 
-    eprintln!("Processing video with config: {config:#?}");
+fn run_video_job(mut config: VideoConfig, job: &VideoJob, reporter: &mut JobReporter) -> VideoResult<()> {
+    debug!(?config, "Processing video");
+
+    if let Some(device) = config.capture_device.clone() {
+        return play_capture_device(&device, config, reporter);
+    }
 
     let video_path = config.video_path.as_str();
-    check_valid_file(video_path);
 
     let output_video_path = config.output_video_path.as_ref();
     let output_video_file: bool = output_video_path.is_some();
 
-    if output_video_file {
-        check_file_exists(output_video_path.unwrap(), config.overwrite);
+    // Only the first output is written to
+    let output_path = output_video_path
+        .or(config.output_cast_path.as_ref())
+        .or(config.output_text_path.as_ref())
+        .or(config.output_export_path.as_ref());
+    if let Some(output_path) = output_path {
+        check_file_exists(output_path, config.overwrite).map_err(|e| Error::VideoWriteError(e.to_string()))?;
     }
 
-    let mut reader = if config.use_opencv {
-        VideoReader::OpenCV(OpenCVVideoReader::new(video_path)?)
+    if is_text_video(video_path) {
+        return render_text_video(&config);
+    }
+    let image_sequence = is_image_sequence(video_path);
+    if !image_sequence {
+        check_valid_file(video_path).map_err(|e| Error::VideoReadError(e.to_string()))?;
+    }
+
+    let mut reader = if image_sequence {
+        VideoReader::ImageSequence(ImageSequenceReader::new(video_path, config.sequence_fps)?)
+    } else if is_stdio(video_path) {
+        if config.use_opencv {
+            return Err(Error::VideoReadError("OpenCV can't read videos from stdin, use ffmpeg instead".to_string()));
+        }
+        VideoReader::FFmpeg(FFmpegVideoReader::from_stdin(config.stdin.clone(), config.input_format.as_deref())?)
+    } else if config.use_opencv {
+        open_opencv_reader(video_path, false)?
     } else {
         VideoReader::FFmpeg(FFmpegVideoReader::new(video_path)?)
     };
 
-    let num_frames = reader.total_frames();
-    unsafe {
-        TOTAL_FRAMES = num_frames;
-    }
-
-    if num_frames == 0 {
+    let total_frames = reader.total_frames();
+    if total_frames == 0 {
         return Ok(());
     }
 
     let orig_fps = reader.fps();
     let frame_time = 1.0 / orig_fps;
+    let range = FrameRange::new(&config, orig_fps, total_frames)?;
+    let num_frames = range.len(total_frames);
+    reporter.probe_finished(reader.frame_size(), orig_fps, total_frames);
 
-    let stdout = io::stdout();
-    let mut handle = stdout.lock();
-    let clear_command = format!("{esc}c", esc = 27 as char);
-
-    let frame_cut = orig_fps as u64 / config.max_fps;
     config.should_rotate = config.rotate > -1 && config.rotate < 3;
+    if output_video_path.is_some_and(|path| is_live_output(path)) {
+        return stream_live(&mut reader, &config, reporter);
+    }
+
+    // Streams of unknown length are read until they end, or until interrupted
+    let unknown_length = num_frames == u64::MAX;
+    let interrupt = StreamInterrupt::register(unknown_length && config.has_output())?;
+
+    let frame_cut = output_frame_cut(&config, orig_fps);
 
     if output_video_file {
-        eprintln!("Encoding video from {} to ascii video at {}", video_path, output_video_path.unwrap());
+        info!("Encoding video from {} to ascii video at {}", video_path, output_video_path.unwrap());
 
         // let pool = ThreadPoolBuilder::new()
         //     .num_threads(config.num_threads as usize) // tune this based on available RAM
         //     .build()
         //     .unwrap();
 
-        // Triple progress bar for reading, encoding, then writing frames
-        let progressbar = ProgressBar::new(num_frames * 3);
-
-        (0..num_frames)
-            .into_iter()
-            .map(|i| {
-                // Process first frame to get output dimensions and initialize video writer
-                eprintln!("Reading frame {} of {num_frames}", i + 1);
-
-                unsafe {
-                    if CANCEL_REQUESTED {
-                        CANCEL_REQUESTED = false;
-                        return Err(Error::Cancelled);
-                    }
-                    READ_CURRENT_FRAME += 1;
-                }
-
-                reader.read_frame(&config).inspect_err(|_e| {
-                    eprintln!("Error reading frame {i} from input video",);
-                })?;
+        // Frames are read, encoded, then written.  Streams of unknown length only get a length once they have
+        // been read.
+        reporter.stage(Stage::Reading, num_frames);
+        reporter.set_steps(0, num_frames.saturating_mul(3));
+
+        let mut frames_read = 0;
+        for (n, i) in range.frame_indices().enumerate() {
+            // Process first frame to get output dimensions and initialize video writer
+            if unknown_length {
+                trace!("Reading frame {}", n + 1);
+            } else {
+                trace!("Reading frame {} of {num_frames}", n + 1);
+            }
 
-                progressbar.inc(1);
-                unsafe {
-                    PROGRESS_PERCENTAGE = progressbar.position() as f32 / progressbar.length().unwrap() as f32;
-                }
+            job.cancellation_token().check()?;
+            if interrupt.interrupted() {
+                break;
+            }
 
-                Ok(())
-            })
-            .collect::<VideoResult<()>>()?;
+            range.advance(&mut reader, n)?;
+            let read = reader.read_frame(&config).inspect_err(|_e| {
+                error!("Error reading frame {i} from input video");
+            })?;
+            if !read {
+                break;
+            }
+            frames_read += 1;
+            reporter.frame_read(n as u64);
+        }
 
         reader.finish()?;
-
-        let mut writer = VideoWriter::try_from((&config, reader))?;
-
-        unsafe {
-            ENCODE_CURRENT_FRAME = 1;
+        // The frame count may have been estimated from the duration, so the exact count is known once read
+        let num_frames = frames_read;
+        if num_frames == 0 {
+            return Ok(());
         }
+        reporter.set_steps(num_frames, num_frames * 3);
 
-        progressbar.inc(1);
-        unsafe {
-            PROGRESS_PERCENTAGE = progressbar.position() as f32 / progressbar.length().unwrap() as f32;
-        }
+        reporter.stage(Stage::Encoding, num_frames);
+        let mut writer = VideoWriter::try_from((&config, reader))?;
+        reporter.frame_encoded(0);
 
         //pool.install(|| {
         (1..num_frames)
             .into_iter()
             .filter_map(|i| {
-                // eprintln to prevent buffering issues with rayon
-                eprintln!("Encoding frame {} of {num_frames}", i + 1);
+                trace!("Encoding frame {} of {num_frames}", i + 1);
                 //std::io::stdout().flush().unwrap();
 
-                if config.use_max_fps_for_output_video && i % frame_cut == 0 {
+                if frame_cut.is_some_and(|frame_cut| i % frame_cut == 0) {
                     return None;
                 }
 
                 // Check for cancellation
-                unsafe {
-                    if CANCEL_REQUESTED {
-                        return Some(Err(Error::Cancelled));
-                    }
-                    ENCODE_CURRENT_FRAME += 1;
+                if let Err(e) = job.cancellation_token().check() {
+                    return Some(Err(e));
                 }
-
                 let res = writer.encode_frame(&config, i as usize);
-                progressbar.inc(1);
-                unsafe {
-                    PROGRESS_PERCENTAGE = progressbar.position() as f32 / progressbar.length().unwrap() as f32;
-                }
+                reporter.frame_encoded(i);
                 Some(res)
             })
             .collect::<VideoResult<()>>()?;
         //})?;
 
-        unsafe {
-            if CANCEL_REQUESTED {
-                return Err(Error::Cancelled);
-            }
-        }
+        job.cancellation_token().check()?;
 
+        reporter.stage(Stage::Writing, num_frames);
         for i in 0..num_frames {
-            eprintln!("Writing frame {} of {num_frames}", i + 1);
+            trace!("Writing frame {} of {num_frames}", i + 1);
 
-            if config.use_max_fps_for_output_video && i as u64 % frame_cut == 0 {
+            if frame_cut.is_some_and(|frame_cut| i % frame_cut == 0) {
                 continue;
             }
 
-            unsafe {
-                if CANCEL_REQUESTED {
-                    return Err(Error::Cancelled);
-                }
-                WRITE_CURRENT_FRAME += 1;
+            job.cancellation_token().check()?;
+            writer.write_frame(i as usize)?;
+            reporter.frame_written(i);
+        }
+
+        // Writes the video explicitly just for clarity
+        writer.close()?;
+
+        info!("Finished writing output video file to {}", output_video_path.unwrap());
+    } else if let Some(cast_path) = config.output_cast_path.as_ref() {
+        info!("Writing ascii frames from {} to asciinema cast at {}", video_path, cast_path);
+
+        reporter.stage(Stage::Converting, num_frames);
+        reporter.set_steps(0, num_frames);
+        // Cast dimensions come from the first frame, so the writer is created lazily
+        let mut cast_writer: Option<CastWriter> = None;
+        for (n, i) in range.frame_indices().enumerate() {
+            job.cancellation_token().check()?;
+            range.advance(&mut reader, n)?;
+            let Some(ascii) = reader.read_frame_as_ascii(&config)? else {
+                break;
+            };
+            if interrupt.interrupted() {
+                break;
             }
 
-            writer.write_frame(i as usize)?;
+            if cast_writer.is_none() {
+                cast_writer = Some(CastWriter::new(cast_path, &ascii)?);
+            }
+            cast_writer.as_mut().unwrap().write_frame((i - range.start) as f64 * frame_time, &ascii)?;
+            reporter.frame_written(n as u64);
+        }
+
+        if let Some(mut cast_writer) = cast_writer {
+            cast_writer.close()?;
+        }
 
-            progressbar.inc(1);
-            unsafe {
-                PROGRESS_PERCENTAGE = progressbar.position() as f32 / progressbar.length().unwrap() as f32;
+        info!("Finished writing asciinema cast to {}", cast_path);
+    } else if let Some(text_path) = config.output_text_path.as_ref() {
+        info!("Writing ascii frames from {} to text video at {}", video_path, text_path);
+
+        reporter.stage(Stage::Converting, num_frames);
+        reporter.set_steps(0, num_frames);
+        let mut text_writer =
+            TextVideoWriter::new(text_path, config.output_fps(orig_fps), config.text_frames_as_directory)?;
+        for (n, _) in range.frame_indices().enumerate() {
+            job.cancellation_token().check()?;
+            range.advance(&mut reader, n)?;
+            let Some(ascii) = reader.read_frame_as_ascii(&config)? else {
+                break;
+            };
+            if interrupt.interrupted() {
+                break;
             }
+            text_writer.write_frame(n as u64, &ascii)?;
+            reporter.frame_written(n as u64);
         }
+        text_writer.close()?;
+
+        info!("Finished writing text video to {}", text_path);
+    } else if let Some(export_path) = config.output_export_path.as_ref() {
+        info!("Exporting ascii frames from {} to {}", video_path, export_path);
+
+        reporter.stage(Stage::Converting, num_frames);
+        reporter.set_steps(0, num_frames);
+        let mut export = AsciiExport::new(config.invert, Some(orig_fps));
+        // Frames keep their index and timestamp in the source video
+        for (n, i) in range.frame_indices().enumerate() {
+            job.cancellation_token().check()?;
+            range.advance(&mut reader, n)?;
+            let Some(ascii) = reader.read_frame_as_ascii(&config)? else {
+                break;
+            };
+            if interrupt.interrupted() {
+                break;
+            }
 
-        // Writes the video explicitly just for clarity
-        writer.close()?;
-        progressbar.finish();
-        unsafe {
-            PROGRESS_PERCENTAGE = 1.0;
+            let mut frame = AsciiExportFrame::new(i, i as f64 * frame_time, &ascii);
+            if config.export_colors {
+                frame = frame.with_colors(&ascii);
+            }
+            if config.export_luminance {
+                frame = frame.with_luminance(&ascii);
+            }
+            export.push_frame(frame);
+            reporter.frame_written(n as u64);
         }
 
-        eprintln!("Finished writing output video file to {}", output_video_path.unwrap());
-    } else {
-        for i in 0..num_frames {
-            let start = SystemTime::now();
+        let format = config.export_format.or_else(|| ExportFormat::from_path(export_path)).unwrap_or_default();
+        export
+            .write(export_path, format)
+            .map_err(|e| Error::VideoWriteError(format!("Could not write export to {export_path}: {e}")))?;
 
-            let ascii = reader.read_frame_as_ascii(&config)?;
+        info!("Finished exporting ascii frames to {}", export_path);
+    } else if let Some(frames_path) = config.output_frames_path.as_ref() {
+        info!("Writing ascii frames from {} as images to {}", video_path, frames_path);
 
-            // Write to terminal
+        let first_frame_path = image_sequence_frame_path(frames_path, 0);
+        if let Some(dir) = first_frame_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| {
+                Error::VideoWriteError(format!("Could not create output directory {}: {e}", dir.display()))
+            })?;
+        }
 
-            if i % frame_cut == 0 {
-                let ascii_str = ascii_to_str(&ascii);
-                write!(handle, "{}", clear_command).unwrap();
-                write!(handle, "{}", ascii_str).unwrap();
+        reporter.stage(Stage::Converting, num_frames);
+        reporter.set_steps(0, num_frames);
+        for (n, _) in range.frame_indices().enumerate() {
+            job.cancellation_token().check()?;
+            range.advance(&mut reader, n)?;
+            let Some(ascii) = reader.read_frame_as_ascii(&config)? else {
+                break;
+            };
+            if interrupt.interrupted() {
+                break;
             }
 
-            let elapsed = start.elapsed().unwrap().as_secs_f64();
-            if elapsed < frame_time {
-                sleep(Duration::from_millis(((frame_time - elapsed) * 1000.0) as u64));
+            // Frames are checked as they're written, as an existing output directory may hold other files
+            let frame_path = image_sequence_frame_path(frames_path, n as u64);
+            if !config.overwrite && frame_path.exists() {
+                return Err(Error::VideoWriteError(format!(
+                    "Frame at {} already exists, and overwrite is set to false",
+                    frame_path.display()
+                )));
             }
+            let (width, height) = get_size_from_ascii(&ascii, config.height_sample_scale, config.font_size);
+            generate_ascii_image(&ascii, width, height, config.invert, config.font_size).save(&frame_path).map_err(
+                |e| Error::VideoWriteError(format!("Could not save ascii frame to {}: {e}", frame_path.display())),
+            )?;
+            reporter.frame_written(n as u64);
         }
+
+        info!("Finished writing ascii frames to {}", frames_path);
+    } else {
+        reporter.stage(Stage::Playing, num_frames);
+        play_in_terminal(&mut reader, &mut config)?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_fps: u64, use_max_fps_for_output_video: bool) -> VideoConfig {
+        VideoConfigBuilder::default()
+            .max_fps(max_fps)
+            .use_max_fps_for_output_video(use_max_fps_for_output_video)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn drops_frames_only_from_sources_faster_than_max_fps() {
+        assert_eq!(output_frame_cut(&config(10, true), 60.0), Some(6));
+        assert_eq!(output_frame_cut(&config(10, true), 29.97), Some(2));
+        assert_eq!(output_frame_cut(&config(10, false), 60.0), None);
+    }
+
+    #[test]
+    fn keeps_every_frame_of_low_fps_sources() {
+        for fps in [1.0, 9.99, 10.0, 15.0, 19.99] {
+            assert_eq!(output_frame_cut(&config(10, true), fps), None, "{fps}");
+        }
+    }
+}